@@ -1,20 +1,48 @@
+use std::{cell::RefCell, rc::Rc};
+
 use cursive::{
-    traits::Finder,
-    view::{ScrollStrategy, Selector},
-    views::{HideableView, PaddedView, TextView},
+    traits::Resizable as _,
+    view::ScrollStrategy,
+    views::{Dialog, EditView},
     Cursive, View,
 };
 
-use crate::{entry::Entry, get_config, ui::*, Config};
+use crate::{
+    config::{IgnoreMode, KeyBinds, Keyword, Matcher},
+    entry::Entry,
+    twitch::{Request, Update},
+    ui::*,
+    Action, Config, Context,
+};
 
 pub struct App;
 impl App {
+    /// How many of the newest rows are looked at for a repeat
+    const MAX_SCANNED: usize = 200;
+
+    /// The context that the ui was built with, kept in the cursive user data
+    pub fn context(cursive: &mut Cursive) -> Context {
+        cursive
+            .user_data::<Context>()
+            .cloned()
+            .expect("the context must be in the cursive user data")
+    }
+
     pub fn quit(cursive: &mut Cursive) {
         cursive.quit()
     }
 
     pub fn select_tab(cursive: &mut Cursive, index: usize) {
-        TabBar::with(cursive).select(index)
+        TabBar::with(cursive).select(index);
+        HelpView::with(cursive).refresh();
+    }
+
+    pub fn toggle_help(cursive: &mut Cursive) {
+        let mut help = HelpView::with(cursive);
+        match help.is_open() {
+            true => help.close(),
+            false => help.open(),
+        }
     }
 
     pub fn focus_status_view(cursive: &mut Cursive) {
@@ -33,12 +61,151 @@ impl App {
         MainScreen::with(cursive).focus_highlights_view()
     }
 
+    pub fn focus_stats_view(cursive: &mut Cursive) {
+        MainScreen::with(cursive).focus_stats_view()
+    }
+
+    /// Binds the configured keys, removing the `previous` bindings first
+    pub fn bind_keys(cursive: &mut Cursive, previous: Option<&KeyBinds>) {
+        if let Some(keybinds) = previous {
+            for event in Matcher::new(keybinds).events() {
+                cursive.clear_global_callbacks(event);
+            }
+        }
+
+        let context = Self::context(cursive);
+        let matcher = Rc::new(RefCell::new(Matcher::new(&context.config().keybinds)));
+        let events = matcher.borrow().events();
+        for event in events {
+            let matcher = Rc::clone(&matcher);
+            cursive.set_global_callback(event.clone(), move |cursive| {
+                let action = matcher.borrow_mut().feed(event.clone());
+                if let Some(action) = action {
+                    Self::action_callback(action)(cursive);
+                    // for the status lines the action added
+                    TabBar::with(cursive).refresh_unread()
+                }
+            });
+        }
+    }
+
+    fn action_callback(action: Action) -> fn(&mut Cursive) {
+        match action {
+            Action::FocusStatusView => Self::focus_status_view,
+            Action::FocusMessagesView => Self::focus_messages_view,
+            Action::FocusLinksView => Self::focus_links_view,
+            Action::FocusHighlightsView => Self::focus_highlights_view,
+            Action::FocusStatsView => Self::focus_stats_view,
+            Action::Quit => Self::quit,
+            Action::ToggleTimestamp => Self::toggle_timestamp,
+            Action::ToggleBadges => Self::toggle_badges,
+            Action::IgnoreSelected => Self::ignore_selected,
+            Action::TogglePoll => Self::toggle_poll,
+            Action::OpenPrompt => Self::open_prompt,
+            Action::ReloadConfig => Self::reload_config,
+            Action::ScrollUp => |cursive| MainScreen::with(cursive).scroll(Scroll::Up),
+            Action::ScrollDown => |cursive| MainScreen::with(cursive).scroll(Scroll::Down),
+            Action::PageUp => |cursive| MainScreen::with(cursive).scroll(Scroll::PageUp),
+            Action::PageDown => |cursive| MainScreen::with(cursive).scroll(Scroll::PageDown),
+            Action::JumpTop => |cursive| MainScreen::with(cursive).scroll(Scroll::Top),
+            Action::JumpBottom => |cursive| MainScreen::with(cursive).scroll(Scroll::Bottom),
+            Action::NextTab => |cursive| MainScreen::with(cursive).focus_next(),
+            Action::PrevTab => |cursive| MainScreen::with(cursive).focus_previous(),
+            Action::ClearView => Self::clear_view,
+            Action::ToggleHelp => Self::toggle_help,
+        }
+    }
+
+    /// Loads the configuration again and applies it, keeping the current one if it has problems
+    pub fn reload_config(cursive: &mut Cursive) {
+        let context = Self::context(cursive);
+        let channel = context.channel();
+        let mut config = match Config::load(channel.as_deref()) {
+            Ok(config) => config,
+            Err(err) => {
+                let err = format!("config wasn't reloaded: {:#}", err);
+                return StatusView::with(cursive).append(Status::Error(err));
+            }
+        };
+
+        // users ignored during this session aren't in the files unless they were persisted
+        for login in context.ignored().iter() {
+            if !config.ignore.contains_user(login) {
+                config.ignore.users.push(login.clone())
+            }
+        }
+
+        let previous = std::mem::replace(&mut *context.config_mut(), config);
+        Self::bind_keys(cursive, Some(&previous.keybinds));
+
+        let active = MainScreen::with(cursive).active_index();
+        Self::select_tab(cursive, active);
+
+        Self::restyle_entries(cursive);
+        Self::hide_ignored(cursive);
+        PollView::with(cursive).refresh();
+        StatusView::with(cursive).append(Status::Reloaded);
+    }
+
+    /// Rebuilds the entries in the lists with the current configuration
+    fn restyle_entries(cursive: &mut Cursive) {
+        fn restyle<V>(
+            lv: &mut ScrollableList,
+            context: &Context,
+            build: impl Fn(&Entry, &Context) -> Option<V>,
+        ) where
+            V: View,
+        {
+            for view in lv.get_inner_mut().children_mut() {
+                if let Some(view) = view.downcast_mut::<EntryView>() {
+                    if let Some(new) = build(view.entry(), context) {
+                        view.restyle(context, new)
+                    }
+                }
+            }
+        }
+
+        let context = &Self::context(cursive);
+        MessagesView::with(cursive).on(|view| restyle(view, context, Entry::as_message_view));
+        LinksView::with(cursive).on(|view| restyle(view, context, Entry::as_links_view));
+        HighlightsView::with(cursive).on(|view| restyle(view, context, Entry::as_highlights_view));
+    }
+
+    // the rows read these when they're laid out
     pub fn toggle_timestamp(cursive: &mut Cursive) {
-        cursive.seek_and_toggle::<TextView, _>("timestamp", |s| &mut s.timestamps)
+        let context = Self::context(cursive);
+        let mut config = context.config_mut();
+        config.timestamps = !config.timestamps
     }
 
     pub fn toggle_badges(cursive: &mut Cursive) {
-        cursive.seek_and_toggle::<PaddedView<TextView>, _>("badge", |s| &mut s.badges)
+        let context = Self::context(cursive);
+        let mut config = context.config_mut();
+        config.badges = !config.badges
+    }
+
+    pub fn apply_updates(cursive: &mut Cursive, updates: Vec<Update>) {
+        for update in updates {
+            match update {
+                Update::Raw(raw) => Self::append_raw(cursive, raw),
+                Update::Append(entry) => Self::append_entry(cursive, entry),
+                Update::Connecting => Self::on_connecting(cursive),
+                Update::Connected(name) => Self::on_connected(cursive, name),
+                Update::Ping => Self::on_ping(cursive),
+                Update::Pong => Self::on_pong(cursive),
+                Update::Joining(channel) => Self::on_joining(cursive, channel),
+                Update::Joined(channel) => Self::on_joined(cursive, channel),
+                Update::Parting(channel) => Self::on_parting(cursive, channel),
+                Update::Parted(channel) => Self::on_parted(cursive, channel),
+                Update::Notice(notice) => Self::append_note(cursive, notice.message),
+                Update::RoomState(..) | Update::Moderation(..) => {}
+                Update::Error(error) => Self::on_error(cursive, error),
+                Update::Disconnected(reason) => Self::on_disconnected(cursive, reason),
+            }
+        }
+
+        // the entries only counted what is unread
+        TabBar::with(cursive).refresh_unread()
     }
 
     pub fn append_raw(cursive: &mut Cursive, raw: String) {
@@ -47,45 +214,293 @@ impl App {
 
     #[track_caller]
     pub fn append_entry(cursive: &mut Cursive, entry: Entry) {
-        fn append<V>(lv: &mut ScrollableList, view: Option<V>)
+        fn append<V>(lv: &mut ScrollableList, entry: &Entry, view: Option<V>) -> bool
         where
             V: View,
         {
+            let appended = view.is_some();
             if let Some(view) = view {
-                lv.get_inner_mut().add_child(view);
-                if lv.is_at_bottom() {
+                lv.get_inner_mut()
+                    .add_child(EntryView::new(entry.clone(), view));
+                if lv.get_inner().focus().is_none() && lv.is_at_bottom() {
                     lv.set_scroll_strategy(ScrollStrategy::StickToBottom);
                 }
             }
+            appended
+        }
+
+        let context = &Self::context(cursive);
+        let ignored = {
+            let ignore = &context.config().ignore;
+            entry.is_ignored(ignore).then_some(ignore.mode)
+        };
 
-            let Config {
-                timestamps, badges, ..
-            } = *get_config();
-
-            <_>::into_iter([
-                ("timestamp", timestamps), //
-                ("badge", badges),
-            ])
-            .for_each(|(k, v)| {
-                lv.call_on_all(&Selector::Name(k), |view: &mut HideableView<TextView>| {
-                    view.set_visible(v)
+        match ignored {
+            Some(IgnoreMode::Hide) => return,
+            Some(IgnoreMode::Collapse) => {
+                MessagesView::with(cursive).on(|view| {
+                    append(view, &entry, Some(entry.as_ignored_view()));
                 });
-            });
+                TabBar::with(cursive).count_unread(MainScreen::MESSAGE_VIEW_INDEX, false);
+                return;
+            }
+            None => {}
         }
 
-        MessagesView::with(cursive).on(|view| {
-            append(view, entry.as_message_view());
+        crate::overlay::publish(context, &entry);
+        Self::record_stats(cursive, &entry);
+        Self::count_vote(cursive, &entry);
+
+        let messages = MessagesView::with(cursive).on(|view| {
+            Self::collapse_repeat(view, context, &entry)
+                || append(view, &entry, entry.as_message_view(context))
         });
 
-        LinksView::with(cursive).on(|view| {
-            append(view, entry.as_links_view());
+        let links = LinksView::with(cursive).on(|view| {
+            append(view, &entry, entry.as_links_view(context)) //
         });
 
+        let highlights = HighlightsView::with(cursive)
+            .on(|view| append(view, &entry, entry.as_highlights_view(context)));
+
+        let our_name = context.our_name();
+        let alert = entry.mentions_us(&context.config().highlights, our_name.as_deref());
+        <_>::into_iter([
+            (MainScreen::MESSAGE_VIEW_INDEX, messages),
+            (MainScreen::LINKS_VIEW_INDEX, links),
+            (MainScreen::HIGHLIGHTS_VIEW_INDEX, highlights),
+        ])
+        .filter(|&(_, appended)| appended)
+        .for_each(|(index, _)| {
+            TabBar::with(cursive).count_unread(index, alert);
+        });
+
+        Self::notify(cursive, &entry)
+    }
+
+    fn count_vote(cursive: &mut Cursive, entry: &Entry) {
+        let counted = Self::context(cursive)
+            .poll()
+            .as_mut()
+            .is_some_and(|poll| poll.vote(entry));
+
+        if counted {
+            PollView::with(cursive).refresh()
+        }
+    }
+
+    fn record_stats(cursive: &mut Cursive, entry: &Entry) {
+        let context = Self::context(cursive);
+        context.stats().record(entry, &context.config());
+
+        if MainScreen::with(cursive).active_index() == MainScreen::STATS_VIEW_INDEX {
+            StatsView::with(cursive).refresh()
+        }
+    }
+
+    fn notify(cursive: &mut Cursive, entry: &Entry) {
+        let context = Self::context(cursive);
+        let result = {
+            let config = context.config();
+            let (our_name, channel) = (context.our_name(), context.channel());
+            match entry.notification(&config, our_name.as_deref()) {
+                Some(action) => crate::notify::notify(
+                    &mut context.notified(),
+                    action,
+                    config.notifications.rate_limit,
+                    entry,
+                    channel.as_deref().unwrap_or_default(),
+                ),
+                None => return,
+            }
+        };
+
+        if let Err(err) = result {
+            StatusView::with(cursive).append(Status::Error(format!("notification: {:#}", err)))
+        }
+    }
+
+    fn collapse_repeat(lv: &mut ScrollableList, context: &Context, entry: &Entry) -> bool {
+        let config = context.config();
+        let dedup = config.dedup;
+        if !dedup.enabled {
+            return false;
+        }
+
+        let window = dedup.window();
+        let repeat = lv
+            .get_inner_mut()
+            .children_mut()
+            .iter_mut()
+            .rev()
+            .take(Self::MAX_SCANNED)
+            .flat_map(|view| view.downcast_mut::<EntryView>())
+            // a collapsed row stays where it first appeared, so older rows can still be recent
+            .filter(|view| entry.ts - view.last_seen() <= window)
+            .filter(|view| !view.entry().is_ignored(&config.ignore))
+            .find(|view| view.entry().similarity(entry) >= dedup.similarity);
+
+        match repeat {
+            Some(view) => {
+                // the repeated view reads the configuration again
+                drop(config);
+                view.repeat(context, entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn follow_focus(cursive: &mut Cursive) {
+        MainScreen::with(cursive).on_active_list(|view| {
+            let strategy = match view.get_inner().focus() {
+                Some(..) => ScrollStrategy::KeepRow,
+                None => ScrollStrategy::StickToBottom,
+            };
+            view.set_scroll_strategy(strategy);
+            view.scroll_to_important_area();
+        });
+    }
+
+    /// Asks for the options of a new poll, or ends the running one
+    pub fn toggle_poll(cursive: &mut Cursive) {
+        if Self::context(cursive).poll().is_some() {
+            return Self::end_poll(cursive);
+        }
+
+        let edit = EditView::new()
+            .on_submit(|cursive, input| {
+                cursive.pop_layer();
+                if let Err(err) = Self::start_poll(cursive, input) {
+                    StatusView::with(cursive).append(Status::Error(format!("poll: {:#}", err)));
+                    TabBar::with(cursive).refresh_unread()
+                }
+            })
+            .min_width(30);
+
+        cursive.add_layer(
+            Dialog::around(edit)
+                .title("poll options, separated by spaces (default: 1 2)")
+                .dismiss_button("Cancel"),
+        )
+    }
+
+    pub fn start_poll(cursive: &mut Cursive, options: &str) -> anyhow::Result<()> {
+        let poll = crate::poll::Poll::parse(options)?;
+        {
+            let context = Self::context(cursive);
+            let mut current = context.poll();
+            anyhow::ensure!(current.is_none(), "a poll is already running");
+            current.replace(poll);
+        }
+        PollView::with(cursive).refresh();
+        Ok(())
+    }
+
+    /// Ends the running poll, putting its result in the status view
+    pub fn end_poll(cursive: &mut Cursive) {
+        let poll = Self::context(cursive).poll().take();
+        if let Some(poll) = poll {
+            StatusView::with(cursive).append(Status::Poll(poll.summary()));
+        }
+        PollView::with(cursive).refresh();
+    }
+
+    pub fn ignore_selected(cursive: &mut Cursive) {
+        let login = MainScreen::with(cursive).on_active_list(|view| {
+            view.get_inner_mut()
+                .focused_mut::<EntryView>()
+                .map(|view| view.entry().login.clone())
+        });
+
+        let login = match login.flatten() {
+            Some(login) => login,
+            None => return,
+        };
+
+        let context = Self::context(cursive);
+        let persist = {
+            let mut config = context.config_mut();
+            let added = !config.ignore.contains_user(&login);
+            if added {
+                config.ignore.users.push(login.clone());
+                context.ignored().push(login.clone());
+            }
+            added && config.ignore.persist
+        };
+
+        Self::hide_ignored(cursive);
+        Self::follow_focus(cursive);
+        StatusView::with(cursive).append(Status::Ignored(login.clone()));
+
+        if persist {
+            if let Err(err) = Config::save_ignored(&login) {
+                StatusView::with(cursive).append(Status::Error(format!("{:#}", err)));
+            }
+        }
+    }
+
+    /// Applies the ignore list to the entries that are already shown
+    pub fn hide_ignored(cursive: &mut Cursive) {
+        let context = Self::context(cursive);
+        let config = context.config();
+
+        let keep = |view: &mut dyn View| match view.downcast_mut::<EntryView>() {
+            Some(view) => !view.entry().is_ignored(&config.ignore),
+            None => true,
+        };
+
+        MessagesView::with(cursive).on(|view| match config.ignore.mode {
+            IgnoreMode::Hide => view.get_inner_mut().retain(keep),
+            IgnoreMode::Collapse => {
+                for view in view.get_inner_mut().children_mut() {
+                    if let Some(view) = view.downcast_mut::<EntryView>() {
+                        if view.entry().is_ignored(&config.ignore) {
+                            let collapsed = view.entry().as_ignored_view();
+                            view.set_view(collapsed)
+                        }
+                    }
+                }
+            }
+        });
+
+        LinksView::with(cursive).on(|view| view.get_inner_mut().retain(keep));
+        HighlightsView::with(cursive).on(|view| view.get_inner_mut().retain(keep));
+    }
+
+    pub fn add_keyword(cursive: &mut Cursive, word: String) {
+        let context = Self::context(cursive);
+        let mut config = context.config_mut();
+        let style = config.highlights.mention;
+        config
+            .highlights
+            .keywords
+            .push(Keyword::new(word).style(style));
+    }
+
+    pub fn append_note(cursive: &mut Cursive, note: String) {
+        StatusView::with(cursive).append(Status::Note(note))
+    }
+
+    /// The entries in the highlights view, oldest first
+    pub fn dump_highlights(cursive: &mut Cursive) -> serde_json::Value {
+        let channel = Self::context(cursive).channel();
+        let channel = channel.as_deref().unwrap_or_default();
+
         HighlightsView::with(cursive).on(|view| {
-            append(view, entry.as_highlights_view());
+            view.get_inner_mut()
+                .children_mut()
+                .iter_mut()
+                .filter_map(|view| view.downcast_mut::<EntryView>())
+                .map(|view| view.entry().as_json(channel))
+                .collect()
         })
     }
 
+    pub fn on_error(cursive: &mut Cursive, error: String) {
+        StatusView::with(cursive).append(Status::Error(error));
+    }
+
     pub fn on_ping(cursive: &mut Cursive) {
         StatusView::with(cursive).append(Status::Ping);
     }
@@ -95,10 +510,12 @@ impl App {
     }
 
     pub fn on_connecting(cursive: &mut Cursive) {
+        ConnectionView::with(cursive).connecting();
         StatusView::with(cursive).append(Status::Connecting);
     }
 
-    pub fn on_connected(cursive: &mut Cursive) {
+    pub fn on_connected(cursive: &mut Cursive, name: String) {
+        Self::context(cursive).set_our_name(name);
         StatusView::with(cursive).append(Status::Connected);
     }
 
@@ -107,13 +524,187 @@ impl App {
     }
 
     pub fn on_joined(cursive: &mut Cursive, channel: String) {
-        crate::state::CONNECTED_CHANNEL
-            .set(channel.clone().into())
-            .expect("single initialize of the channel state");
+        Self::context(cursive).set_channel(Some(&channel));
+        Self::clear_lists(cursive);
         StatusView::with(cursive).append(Status::Joined(channel));
         StatusView::with(cursive).append(Status::Information);
     }
+
+    pub fn on_parting(cursive: &mut Cursive, channel: String) {
+        StatusView::with(cursive).append(Status::Parting(channel));
+    }
+
+    pub fn on_parted(cursive: &mut Cursive, channel: String) {
+        Self::context(cursive).set_channel(None);
+        StatusView::with(cursive).append(Status::Parted(channel));
+    }
+
+    pub fn on_disconnected(cursive: &mut Cursive, reason: String) {
+        Self::context(cursive).set_channel(None);
+        ConnectionView::with(cursive).disconnected(&reason);
+        StatusView::with(cursive).append(Status::Disconnected(reason));
+    }
+
+    pub fn open_prompt(cursive: &mut Cursive) {
+        CommandPrompt::with(cursive).open()
+    }
+
+    pub fn submit_command(cursive: &mut Cursive, line: &str) {
+        CommandPrompt::with(cursive).close();
+        if let Err(err) = Self::run_command(cursive, line) {
+            StatusView::with(cursive).append(Status::Error(format!("{:#}", err)))
+        }
+        TabBar::with(cursive).refresh_unread()
+    }
+
+    /// Runs a line typed into the command prompt
+    pub fn run_command(cursive: &mut Cursive, line: &str) -> anyhow::Result<()> {
+        let (head, tail) = line
+            .trim()
+            .split_once(' ')
+            .map(|(head, tail)| (head, tail.trim()))
+            .unwrap_or((line.trim(), ""));
+
+        match (head, tail) {
+            ("", "") => Ok(()),
+            ("join", channel) => Self::join_channel(cursive, channel),
+            ("part", "") => Self::part_channel(cursive),
+            ("switch", channel) => Self::switch_channel(cursive, channel),
+            (head, ..) => anyhow::bail!("unknown command: '{}'", head),
+        }
+    }
+
+    /// Joins a channel, if we aren't in one
+    pub fn join_channel(cursive: &mut Cursive, channel: &str) -> anyhow::Result<()> {
+        let context = Self::context(cursive);
+        if let Some(current) = context.channel() {
+            anyhow::bail!("already in {}, use 'switch' to change channels", current)
+        }
+        context.send_request(Request::Join(normalize_channel(channel)?))
+    }
+
+    /// Leaves the current channel, keeping its entries around
+    pub fn part_channel(cursive: &mut Cursive) -> anyhow::Result<()> {
+        let context = Self::context(cursive);
+        let current = context
+            .channel()
+            .ok_or_else(|| anyhow::anyhow!("not in a channel"))?;
+        context.send_request(Request::Part(current.to_string()))
+    }
+
+    /// Leaves the current channel, if we're in one, and joins another
+    pub fn switch_channel(cursive: &mut Cursive, channel: &str) -> anyhow::Result<()> {
+        let context = Self::context(cursive);
+        let channel = normalize_channel(channel)?;
+        if let Some(current) = context.channel() {
+            if *current == *channel {
+                anyhow::bail!("already in {}", current)
+            }
+            context.send_request(Request::Part(current.to_string()))?;
+        }
+        context.send_request(Request::Join(channel))
+    }
+
+    /// Clears the active screen, the stats are kept
+    pub fn clear_view(cursive: &mut Cursive) {
+        let index = MainScreen::with(cursive).active_index();
+        match index {
+            MainScreen::STATUS_VIEW_INDEX => StatusView::with(cursive).clear(),
+            MainScreen::STATS_VIEW_INDEX => {}
+            _ => {
+                MainScreen::with(cursive).on_active_list(|view| view.get_inner_mut().clear());
+                TabBar::with(cursive).clear_unread(index);
+                Self::follow_focus(cursive);
+            }
+        }
+    }
+
+    /// Removes the entries of the previous channel
+    fn clear_lists(cursive: &mut Cursive) {
+        MessagesView::with(cursive).on(|view| view.get_inner_mut().clear());
+        LinksView::with(cursive).on(|view| view.get_inner_mut().clear());
+        HighlightsView::with(cursive).on(|view| view.get_inner_mut().clear());
+
+        <_>::into_iter([
+            MainScreen::MESSAGE_VIEW_INDEX,
+            MainScreen::LINKS_VIEW_INDEX,
+            MainScreen::HIGHLIGHTS_VIEW_INDEX,
+        ])
+        .for_each(|index| TabBar::with(cursive).clear_unread(index));
+    }
 }
 
-mod find_view;
-pub use find_view::FindView;
+/// Lowercases the channel and adds the leading `#`
+fn normalize_channel(channel: &str) -> anyhow::Result<String> {
+    let channel = channel.trim().trim_start_matches('#');
+    anyhow::ensure!(!channel.is_empty(), "a channel is needed");
+    anyhow::ensure!(
+        !channel.contains(char::is_whitespace),
+        "'{}' isn't a channel name",
+        channel
+    );
+    Ok(format!("#{}", channel.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cursive::views::{ScrollView, TextView};
+
+    use super::*;
+
+    fn list(messages: &[&str]) -> ScrollableList {
+        let mut list = LimitedListView::limited_to(App::MAX_SCANNED * 2);
+        for message in messages {
+            let entry = Entry::message("museun", message);
+            list.add_child(EntryView::new(entry, TextView::new(*message)));
+        }
+        ScrollView::new(list)
+    }
+
+    fn context() -> Context {
+        let mut config = Config::default();
+        config.dedup.enabled = true;
+        Context::new(config)
+    }
+
+    #[test]
+    fn collapses_a_repeat() {
+        let context = context();
+        let mut list = list(&["hello world", "something else"]);
+
+        let repeat = Entry::message("shaken_bot", "Hello  World");
+        assert!(App::collapse_repeat(&mut list, &context, &repeat));
+
+        let other = Entry::message("shaken_bot", "goodbye world");
+        assert!(!App::collapse_repeat(&mut list, &context, &other));
+    }
+
+    #[test]
+    fn only_when_enabled() {
+        let context = Context::new(Config::default());
+        let mut list = list(&["hello world"]);
+        let repeat = Entry::message("shaken_bot", "hello world");
+        assert!(!App::collapse_repeat(&mut list, &context, &repeat));
+    }
+
+    #[test]
+    fn only_scans_the_newest_rows() {
+        let context = context();
+        let repeat = Entry::message("shaken_bot", "hello world");
+
+        let filler = (0..App::MAX_SCANNED)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        let rows = |newer: usize| {
+            std::iter::once("hello world")
+                .chain(filler[..newer].iter().map(String::as_str))
+                .collect::<Vec<_>>()
+        };
+
+        let mut within = list(&rows(App::MAX_SCANNED - 1));
+        assert!(App::collapse_repeat(&mut within, &context, &repeat));
+
+        let mut beyond = list(&rows(App::MAX_SCANNED));
+        assert!(!App::collapse_repeat(&mut beyond, &context, &repeat));
+    }
+}
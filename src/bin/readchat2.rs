@@ -1,15 +1,15 @@
-use std::sync::{Arc, RwLock};
-
 use readchat2::*;
 
 pub struct Args {
     channel: Option<String>,
     simulated: bool,
     transcribe: bool,
+    no_tui: bool,
+    format: Format,
 }
 
 impl Args {
-    const HEADER: &'static str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    const HEADER: &'static str = readchat2::HEADER;
 
     const SHORT_HELP: &'static str = r#"
 USAGE:
@@ -20,8 +20,11 @@ FLAGS:
     -v, --version               show the current version
     --transcribe                logs all messages to disk
     --simulated                 shows a simulated chat
+    --no-tui                    prints messages to stdout instead of showing the ui
+    --format <text|json>        the output format for --no-tui, defaults to text
     --print-default-config      print the default toml configuration
     --print-config-path         print the default configuration path
+    --check-config [path]       check a configuration file, defaults to the one at the configuration path
     "#;
 
     pub fn parse() -> anyhow::Result<Self> {
@@ -45,81 +48,142 @@ FLAGS:
             println!("{}", Config::config_path()?.to_string_lossy());
             std::process::exit(0);
         }
+
+        if args.contains("--check-config") {
+            let path = match args.finish().pop() {
+                Some(path) => path.into(),
+                None => Config::config_path()?,
+            };
+            std::process::exit(check_config(&path)?);
+        }
         let simulated = args.contains("--simulated");
         let transcribe = args.contains("--transcribe");
+        let no_tui = args.contains("--no-tui");
+        let format = args.opt_value_from_str("--format")?;
+        if format.is_some() && !no_tui {
+            anyhow::bail!("--format can only be used with --no-tui")
+        }
+
         let channel = args.finish().pop().map(|s| s.to_string_lossy().to_string());
         Ok(Self {
             channel,
             simulated,
             transcribe,
+            no_tui,
+            format: format.unwrap_or_default(),
         })
     }
 }
 
-fn new_cursive() -> cursive::CursiveRunnable {
+fn new_cursive(context: &Context) -> cursive::CursiveRunnable {
     let mut cursive = cursive::default();
     cursive.set_theme(colors::sensible_theme());
-    cursive.add_fullscreen_layer(build_ui());
+    cursive.set_user_data(context.clone());
+    cursive.add_fullscreen_layer(build_ui(context));
     cursive
         .focus_name(ui::MessagesView::name())
         .expect("MessageView should be in the tree");
     cursive
 }
 
+/// Prints the problems in the configuration file, returning the exit code
+fn check_config(path: &std::path::Path) -> anyhow::Result<i32> {
+    let problems = Config::check(path)?;
+    if problems.is_empty() {
+        println!("{}: ok", path.display());
+        return Ok(0);
+    }
+
+    for problem in &problems {
+        eprintln!("{}:{}", path.display(), problem);
+    }
+    eprintln!(
+        "{} problem{} found",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    );
+    Ok(1)
+}
+
+#[cfg(unix)]
+fn listen_control(cursive: &mut cursive::Cursive) -> Option<control::ControlSocket> {
+    let control = App::context(cursive).config().control.clone();
+    if !control.enabled {
+        return None;
+    }
+
+    let sink = cursive.cb_sink().clone();
+    match control
+        .socket_path()
+        .and_then(|path| control::listen(&path, sink))
+    {
+        Ok(socket) => Some(socket),
+        Err(err) => {
+            App::on_error(cursive, format!("control socket: {:#}", err));
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn listen_control(cursive: &mut cursive::Cursive) -> Option<()> {
+    if App::context(cursive).config().control.enabled {
+        let err = "control socket: unix sockets aren't available on this platform";
+        App::on_error(cursive, err.to_string());
+    }
+    None
+}
+
 fn main() -> anyhow::Result<()> {
     let Args {
         channel,
         simulated,
         transcribe,
+        no_tui,
+        format,
     } = Args::parse()?;
 
-    panic_logger::setup();
-
-    let config = match std::fs::read(Config::config_path()?) {
-        Ok(data) => Config::from_yaml(data)?,
-        Err(err) if matches!(err.kind(), std::io::ErrorKind::NotFound) => {
-            let config_dir = Config::config_dir()?;
-            let opt_out = config_dir.join(".no_config_wanted");
-            if !opt_out.exists() {
-                eprintln!(
-                    "no configuration file was found at: {}",
-                    Config::config_path()?.to_string_lossy()
-                );
-                eprintln!("if you want to configure the colors / default appearance then:");
-                eprintln!(" 1. mkdir -p $(readchat2 --print-config-path)");
-                eprintln!(
-                    " 2. readchat2 --print-default-config > $(readchat2 --print-config-path)"
-                );
-                eprintln!(" 3. $EDITOR $(readchat2 --print-config-path)");
-
-                eprintln!();
-                eprintln!("this message will only show once.");
-                eprintln!("if you don't care about having a configuration file");
-                eprintln!("then re-run the program and it'll start normally");
-
-                std::fs::create_dir_all(&config_dir)?;
-                std::fs::write(&opt_out, "you're ignoring the configuration option. remove this file to get the warning again")?;
-                std::process::exit(1);
-            }
+    if !Config::config_path()?.exists() {
+        let config_dir = Config::config_dir()?;
+        let opt_out = config_dir.join(".no_config_wanted");
+        if !opt_out.exists() {
+            eprintln!(
+                "no configuration file was found at: {}",
+                Config::config_path()?.to_string_lossy()
+            );
+            eprintln!("if you want to configure the colors / default appearance then:");
+            eprintln!(" 1. mkdir -p $(readchat2 --print-config-path)");
+            eprintln!(" 2. readchat2 --print-default-config > $(readchat2 --print-config-path)");
+            eprintln!(" 3. $EDITOR $(readchat2 --print-config-path)");
 
-            Config::default()
+            eprintln!();
+            eprintln!("this message will only show once.");
+            eprintln!("if you don't care about having a configuration file");
+            eprintln!("then re-run the program and it'll start normally");
+
+            std::fs::create_dir_all(&config_dir)?;
+            std::fs::write(&opt_out, "you're ignoring the configuration option. remove this file to get the warning again")?;
+            std::process::exit(1);
         }
-        Err(err) => return Err(err.into()),
-    };
+    }
+
+    let config = Config::load(channel.as_deref())?;
 
     type Logger = Box<dyn std::io::Write + Send + Sync + 'static>;
 
     let logger: Logger = if channel.as_ref().filter(|_| transcribe).is_some() {
-        let name = Config::data_dir()?
+        let dir = Config::data_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let name = dir
             .join(format!(
                 "{}-{}",
                 channel.as_deref().expect("channel must exist"),
-                std::time::SystemTime::now().elapsed()?.as_secs()
+                std::time::SystemTime::UNIX_EPOCH.elapsed()?.as_secs()
             ))
-            .with_extension(".log");
+            .with_extension("log");
         let file = std::fs::OpenOptions::new()
             .create(true)
-            .write(true)
+            .append(true)
             .open(name)?;
         Box::new(file) as _
     } else {
@@ -146,31 +210,59 @@ fn main() -> anyhow::Result<()> {
         ChatMode::Real(channel)
     };
 
-    let config = Arc::new(RwLock::new(config));
+    let context = Context::new(config);
+    panic_logger::setup(!no_tui, context.clone());
 
-    readchat2::CONFIG
-        .set(Arc::clone(&config))
-        .expect("single initialization of the global configuration");
+    let overlay = context.config().overlay.clone();
+    let overlay = overlay.enabled.then(|| overlay::serve(overlay, &context));
 
-    let mut cursive = new_cursive();
+    if no_tui {
+        match overlay {
+            Some(Ok(addr)) => eprintln!("overlay: http://{}", addr),
+            Some(Err(err)) => eprintln!("error: overlay: {:#}", err),
+            None => {}
+        }
 
-    for (action, binding) in &config.read().unwrap().keybinds.map {
-        let func = match action {
-            Action::FocusStatusView => App::focus_status_view,
-            Action::FocusMessagesView => App::focus_messages_view,
-            Action::FocusLinksView => App::focus_links_view,
-            Action::FocusHighlightsView => App::focus_highlights_view,
-            Action::Quit => App::quit,
-            Action::ToggleTimestamp => App::toggle_timestamp,
-            Action::ToggleBadges => App::toggle_badges,
-        };
-        cursive.set_global_callback(*binding, func);
+        let sink = Headless::new(format, context.clone());
+        let result = chat_mode.connect(&context, logger)(sink)
+            .join()
+            .map_err(|_| anyhow::anyhow!("the connection thread panicked"))?;
+        export_stats(&context);
+        // why the connection ended was already printed
+        std::process::exit(result.is_err() as i32);
     }
 
+    let mut cursive = new_cursive(&context);
+
+    App::bind_keys(&mut cursive, None);
     App::focus_status_view(&mut cursive);
 
-    let sink = cursive.cb_sink().clone();
-    chat_mode.connect(logger)?(sink);
+    match overlay {
+        Some(Ok(addr)) => App::append_note(&mut cursive, format!("overlay: http://{}", addr)),
+        Some(Err(err)) => App::on_error(&mut cursive, format!("overlay: {:#}", err)),
+        None => {}
+    }
+
+    // this is kept alive so the socket is removed when we exit
+    let _control = listen_control(&mut cursive);
+    reload::watch(cursive.cb_sink().clone(), context.clone());
+
+    let sink = UiSink::new(cursive.cb_sink().clone());
+    chat_mode.connect(&context, logger)(sink);
     cursive.run();
+    // the terminal is restored once cursive is dropped
+    drop(cursive);
+    export_stats(&context);
     Ok(())
 }
+
+fn export_stats(context: &Context) {
+    if !context.config().stats.export {
+        return;
+    }
+
+    match stats::export(context) {
+        Ok(path) => eprintln!("stats were written to: {}", path.to_string_lossy()),
+        Err(err) => eprintln!("cannot write stats: {:#}", err),
+    }
+}
@@ -0,0 +1,103 @@
+use std::thread::JoinHandle;
+
+use crate::{
+    config::Keepalive,
+    twitch::{Request, Update, UpdateSink as _},
+    ChatMode,
+};
+
+/// A connection to a channel's chat, without the terminal ui
+///
+/// The updates from it are received from [`Client::updates`], or by iterating over it.
+/// It ends with an [`Update::Disconnected`] when the connection was lost
+///
+/// ```no_run
+/// for update in readchat2::Client::connect("museun") {
+///     if let readchat2::Update::Append(entry) = update {
+///         println!("{}: {}", entry.name, entry.data)
+///     }
+/// }
+/// ```
+pub struct Client {
+    updates: flume::Receiver<Update>,
+    requests: flume::Sender<Request>,
+    handle: JoinHandle<anyhow::Result<()>>,
+}
+
+impl Client {
+    /// Connects anonymously in the background, joining `channel` once connected
+    pub fn connect(channel: &str) -> Self {
+        Self::connect_with(channel, Keepalive::default())
+    }
+
+    /// Connects like [`Client::connect`], keeping the connection alive with `keepalive`
+    pub fn connect_with(channel: &str, keepalive: Keepalive) -> Self {
+        let (mut updates_tx, updates) = flume::unbounded();
+        let (requests, requests_rx) = flume::unbounded();
+
+        let mode = ChatMode::Real(channel.to_string());
+        let handle = std::thread::spawn(move || {
+            let result = mode.run(&mut updates_tx, keepalive, requests_rx, std::io::sink());
+            if let Err(err) = &result {
+                updates_tx.deliver(Update::Disconnected(format!("{:#}", err)));
+            }
+            result
+        });
+
+        Self {
+            updates,
+            requests,
+            handle,
+        }
+    }
+
+    /// The updates, in the order they arrived
+    pub fn updates(&self) -> &flume::Receiver<Update> {
+        &self.updates
+    }
+
+    /// Joins `channel`, an [`Update::Joined`] follows once the server has confirmed it
+    ///
+    /// This fails once the connection has closed
+    pub fn join(&self, channel: &str) -> anyhow::Result<()> {
+        self.request(Request::Join(channel.to_string()))
+    }
+
+    /// Leaves `channel`, an [`Update::Parted`] follows once the server has confirmed it
+    ///
+    /// Like [`Client::join`], this fails once the connection has closed
+    pub fn part(&self, channel: &str) -> anyhow::Result<()> {
+        self.request(Request::Part(channel.to_string()))
+    }
+
+    /// Stops listening, and waits for the connection to close
+    ///
+    /// This returns why the connection ended, if it ended on its own before this
+    pub fn close(self) -> anyhow::Result<()> {
+        let Self {
+            updates,
+            requests,
+            handle,
+        } = self;
+        // the connection stops once nobody can send it requests, or receive its updates
+        drop(requests);
+        drop(updates);
+        handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("the connection thread panicked"))?
+    }
+
+    fn request(&self, request: Request) -> anyhow::Result<()> {
+        self.requests
+            .send(request)
+            .map_err(|_| anyhow::anyhow!("the connection has closed"))
+    }
+}
+
+impl Iterator for Client {
+    type Item = Update;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.updates.recv().ok()
+    }
+}
@@ -1,6 +1,9 @@
-use cursive::theme::{BorderStyle, Color, Palette, PaletteColor, Theme};
+#[cfg(feature = "tui")]
+use cursive::theme::{BorderStyle, Palette, PaletteColor, Theme};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use crate::Color;
+
 #[allow(unused_macros)]
 macro_rules! const_array {
     (@count) => { 0 };
@@ -14,24 +17,25 @@ macro_rules! const_array {
 
 const_array! {
     pub DEFAULT_COLORS ; Color [
-        Color::Rgb(0, 0, 255),     // `Blue`       : `#0000FF`
-        Color::Rgb(138, 43, 226),  // `BlueViolet` : `#8A2BE2`
-        Color::Rgb(95, 158, 160),  // `CadetBlue`  : `#5F9EA0`
-        Color::Rgb(210, 105, 30),  // `Chocolate`  : `#D2691E`
-        Color::Rgb(255, 127, 80),  // `Coral`      : `#FF7F50`
-        Color::Rgb(30, 144, 255),  // `DodgerBlue` : `#1E90FF`
-        Color::Rgb(178, 34, 34),   // `Firebrick`  : `#B22222`
-        Color::Rgb(218, 165, 32),  // `GoldenRod`  : `#DAA520`
-        Color::Rgb(0, 128, 0),     // `Green`      : `#008000`
-        Color::Rgb(255, 105, 180), // `HotPink`    : `#FF69B4`
-        Color::Rgb(255, 69, 0),    // `OrangeRed`  : `#FF4500`
-        Color::Rgb(255, 0, 0),     // `Red`        : `#FF0000`
-        Color::Rgb(46, 139, 87),   // `SeaGreen`   : `#2E8B57`
-        Color::Rgb(0, 255, 127),   // `SpringGreen`: `#00FF7F`
-        Color::Rgb(173, 255, 47),  // `YellowGreen`: `#ADFF2F`
+        Color(0, 0, 255),     // `Blue`       : `#0000FF`
+        Color(138, 43, 226),  // `BlueViolet` : `#8A2BE2`
+        Color(95, 158, 160),  // `CadetBlue`  : `#5F9EA0`
+        Color(210, 105, 30),  // `Chocolate`  : `#D2691E`
+        Color(255, 127, 80),  // `Coral`      : `#FF7F50`
+        Color(30, 144, 255),  // `DodgerBlue` : `#1E90FF`
+        Color(178, 34, 34),   // `Firebrick`  : `#B22222`
+        Color(218, 165, 32),  // `GoldenRod`  : `#DAA520`
+        Color(0, 128, 0),     // `Green`      : `#008000`
+        Color(255, 105, 180), // `HotPink`    : `#FF69B4`
+        Color(255, 69, 0),    // `OrangeRed`  : `#FF4500`
+        Color(255, 0, 0),     // `Red`        : `#FF0000`
+        Color(46, 139, 87),   // `SeaGreen`   : `#2E8B57`
+        Color(0, 255, 127),   // `SpringGreen`: `#00FF7F`
+        Color(173, 255, 47),  // `YellowGreen`: `#ADFF2F`
     ]
 }
 
+#[cfg(feature = "tui")]
 pub fn sensible_theme() -> Theme {
     Theme {
         shadow: false,
@@ -41,12 +45,13 @@ pub fn sensible_theme() -> Theme {
 }
 
 pub fn choose_color() -> Color {
-    thread_local! { static COUNTER: AtomicUsize = AtomicUsize::new(0); }
+    thread_local! { static COUNTER: AtomicUsize = const { AtomicUsize::new(0) }; }
     let n = std::thread::LocalKey::with(&COUNTER, move |c| c.fetch_add(1, Ordering::SeqCst));
     let max = DEFAULT_COLORS.len();
     DEFAULT_COLORS[(n + max - 1) % max]
 }
 
+#[cfg(feature = "tui")]
 pub fn default_palette() -> Palette {
     const_array! {
         pub PALETTE_COLORS ; PaletteColor [
@@ -65,7 +70,7 @@ pub fn default_palette() -> Palette {
     }
 
     <_>::into_iter(PALETTE_COLORS)
-        .zip(std::iter::repeat(Color::TerminalDefault))
+        .zip(std::iter::repeat(cursive::theme::Color::TerminalDefault))
         .fold(Palette::default(), |mut p, (k, v)| {
             p[k] = v;
             p
@@ -1,6 +1,16 @@
 use std::path::PathBuf;
 
 use anyhow::Context as _;
+use serde_yaml::{Mapping, Value};
+
+mod layers;
+
+#[cfg(feature = "tui")]
+mod check;
+#[cfg(feature = "tui")]
+mod edit;
+#[cfg(feature = "tui")]
+pub use check::Problem;
 
 mod colors;
 pub use colors::Colors;
@@ -23,8 +33,40 @@ pub use color::Color;
 mod highlights;
 pub use highlights::{Highlights, Keyword};
 
+mod rule;
+pub use rule::{Rule, Target};
+
+mod dedup;
+pub use dedup::Dedup;
+
+mod ignore;
+pub use ignore::{Ignore, IgnoreMode};
+
+mod notifications;
+pub use notifications::{Notifications, Notify, NotifyCommand, RateLimit};
+
+mod control;
+pub use control::Control;
+
+mod hook;
+pub use hook::Hook;
+
+mod overlay;
+pub use overlay::Overlay;
+
+mod stats;
+pub use stats::Stats;
+
+mod keepalive;
+pub use keepalive::Keepalive;
+
+mod output;
+pub use output::Output;
+
 mod keybinds;
-pub use keybinds::{Action, Input, KeyBinds};
+#[cfg(feature = "tui")]
+pub use keybinds::Matcher;
+pub use keybinds::{Action, Bindings, Input, KeyBinds, Preset, Sequence};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Config {
@@ -37,34 +79,122 @@ pub struct Config {
     pub tab_names: TabNameMapping,
     pub colors: Colors,
     pub highlights: Highlights,
+    #[serde(default)]
+    pub ignore: Ignore,
+    #[serde(default)]
+    pub dedup: Dedup,
+    #[serde(default)]
+    pub notifications: Notifications,
+    #[serde(default)]
+    pub hook: Option<Hook>,
+    #[serde(default)]
+    pub control: Control,
+    #[serde(default)]
+    pub overlay: Overlay,
+    #[serde(default)]
+    pub stats: Stats,
+    #[serde(default)]
+    pub output: Output,
+    #[serde(default)]
+    pub keepalive: Keepalive,
     pub keybinds: KeyBinds,
 }
 
 impl Default for Config {
-    // TODO this should maybe default from the file to ensure they are in sync
     fn default() -> Self {
-        let (channel, tabs, tab_names, badge_names, colors, highlights, keybinds) = <_>::default();
-
-        Self {
-            timestamps: true,
-            badges: true,
-            timestamp_fmt: "%X".into(),
-
-            badge_names,
-            channel,
-            tabs,
-            tab_names,
-            colors,
-            highlights,
-            keybinds,
-        }
+        serde_yaml::from_str(Self::default_config())
+            .expect("the default configuration should be valid")
     }
 }
 
 impl Config {
+    /// Parses a configuration, with anything it leaves out taken from the defaults
     pub fn from_yaml(data: impl AsRef<[u8]>) -> anyhow::Result<Self> {
-        serde_yaml::from_slice(data.as_ref())
-            .with_context(|| anyhow::anyhow!("cannot parse config"))
+        let layer = serde_yaml::from_slice(data.as_ref())
+            .with_context(|| anyhow::anyhow!("cannot parse config"))?;
+        Self::from_layers(<_>::into_iter([layer]))
+    }
+
+    /// Loads the configuration from its layers, each one overriding the ones before it:
+    ///
+    /// 1. the defaults
+    /// 2. the user's `config.yaml`
+    /// 3. `channels/<channel>.yaml` in the configuration directory
+    /// 4. the `READCHAT2_*` environment variables
+    ///
+    /// The channel is the one given, or the one set in `config.yaml` or the environment
+    pub fn load(channel: Option<&str>) -> anyhow::Result<Self> {
+        let user = layers::from_file(&Self::config_path()?)?;
+        let env = Self::from_env(std::env::vars())?;
+
+        let channel = match channel {
+            Some(channel) => Some(channel.to_string()),
+            None => <_>::into_iter([&env])
+                .chain(&user)
+                .find_map(|layer| layer.get("channel").and_then(Value::as_str))
+                .map(ToString::to_string),
+        };
+
+        let channel = match channel.filter(|s| !s.is_empty()) {
+            Some(channel) => layers::from_file(&Self::channel_config_path(&channel)?)?,
+            None => None,
+        };
+
+        Self::from_layers(user.into_iter().chain(channel).chain(Some(env)))
+    }
+
+    /// Merges the `READCHAT2_*` variables into one layer, after checking each one over the defaults
+    fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> anyhow::Result<Value> {
+        let schema = serde_yaml::to_value(Self::default())?;
+
+        let mut env = Value::Mapping(Mapping::new());
+        for (key, layer) in layers::from_env(vars, &schema)? {
+            Self::from_layers(Some(layer.clone()))
+                .with_context(|| anyhow::anyhow!("invalid value for {}", key))?;
+            layers::merge(&mut env, layer);
+        }
+        Ok(env)
+    }
+
+    /// Checks the configuration file at `path`, returning every problem found in it
+    #[cfg(feature = "tui")]
+    pub fn check(path: &std::path::Path) -> anyhow::Result<Vec<Problem>> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| anyhow::anyhow!("cannot read {}", path.display()))?;
+        Ok(check::check(&data))
+    }
+
+    fn from_layers(layers: impl IntoIterator<Item = Value>) -> anyhow::Result<Self> {
+        let mut config = serde_yaml::from_str(Self::default_config())?;
+        for layer in layers {
+            layers::merge(&mut config, layer);
+        }
+        serde_yaml::from_value(config).with_context(|| anyhow::anyhow!("cannot parse config"))
+    }
+
+    /// Adds a user to `ignore.users` in the user's `config.yaml`
+    ///
+    /// Only the new entry is written, so the rest of the file stays as it was
+    #[cfg(feature = "tui")]
+    pub fn save_ignored(login: &str) -> anyhow::Result<()> {
+        let path = Self::config_path()?;
+        let data = match std::fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| anyhow::anyhow!("cannot read {}", path.display()))
+            }
+        };
+
+        let data = edit::add_ignored_user(&data, login)?;
+        std::fs::create_dir_all(Self::config_dir()?)?;
+        std::fs::write(path, data).with_context(|| anyhow::anyhow!("cannot write config"))
+    }
+
+    /// The per-channel overrides, e.g. `channels/museun.yaml`
+    pub fn channel_config_path(channel: &str) -> anyhow::Result<PathBuf> {
+        let name = channel.trim_start_matches('#').to_lowercase();
+        Self::config_dir().map(|p| p.join("channels").join(name).with_extension("yaml"))
     }
 
     pub fn config_path() -> anyhow::Result<PathBuf> {
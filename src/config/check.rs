@@ -0,0 +1,489 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_yaml::{Mapping, Value};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+use super::{layers, Config, Preset, Sequence};
+
+/// Something wrong with a configuration file
+#[derive(Debug)]
+pub struct Problem {
+    pub line: usize,
+    pub column: usize,
+    /// The keys leading to the problem, e.g. `tabs.active.fg`
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Finds every problem in the yaml, as a layer over the defaults
+pub fn check(data: &str) -> Vec<Problem> {
+    // an empty file has nothing to override
+    if data.trim().is_empty() {
+        return vec![];
+    }
+
+    let root = match serde_yaml::from_str::<Value>(data) {
+        Ok(Value::Null) => return vec![],
+        Ok(root) => root,
+        Err(err) => {
+            let (line, column) = err
+                .location()
+                .map(|loc| (loc.line(), loc.column()))
+                .unwrap_or((1, 1));
+            return vec![Problem {
+                line,
+                column,
+                path: String::new(),
+                message: strip_location(&err.to_string()),
+            }];
+        }
+    };
+
+    let mut checker = Checker {
+        positions: Positions::read(data),
+        problems: vec![],
+    };
+    checker.check(&mut vec![], &root);
+    checker.duplicate_bindings(&root);
+    if let Ok(schema) = serde_yaml::to_value(Config::default()) {
+        checker.unknown_keys(&mut vec![], &root, &schema);
+    }
+
+    let mut problems = checker.problems;
+    problems.sort_by_key(|problem| (problem.line, problem.column));
+    problems
+}
+
+struct Binding {
+    action: String,
+    text: String,
+    events: Vec<cursive::event::Event>,
+    path: Vec<String>,
+    /// Whether the binding comes from the file, rather than from the defaults
+    in_file: bool,
+}
+
+struct Checker {
+    positions: Positions,
+    problems: Vec<Problem>,
+}
+
+impl Checker {
+    /// Layers just this value over the defaults, looking deeper for the cause when that fails
+    fn check(&mut self, path: &mut Vec<String>, value: &Value) {
+        let err = match Self::try_layer(path, value.clone()) {
+            Ok(()) => return,
+            Err(err) => err,
+        };
+
+        let before = self.problems.len();
+        match value {
+            Value::Mapping(mapping) => {
+                for (key, value) in mapping {
+                    let key = match key.as_str() {
+                        Some(key) => key.to_string(),
+                        None => continue,
+                    };
+                    path.push(key);
+                    self.check(path, value);
+                    path.pop();
+                }
+            }
+
+            // lists replace each other, so each item is tried as a list of its own
+            Value::Sequence(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if let Err(err) = Self::try_layer(path, Value::Sequence(vec![item.clone()])) {
+                        path.push(index.to_string());
+                        self.report(path, err);
+                        path.pop();
+                    }
+                }
+            }
+
+            _ => {}
+        }
+
+        // nothing deeper is wrong on its own, so it is the combination
+        if self.problems.len() == before {
+            self.report(path, err)
+        }
+    }
+
+    fn try_layer(path: &[String], value: Value) -> Result<(), String> {
+        let mut layer = Value::Mapping(Mapping::new());
+        let keys = path.iter().map(String::as_str).collect::<Vec<_>>();
+        layers::set(&mut layer, &keys, value);
+
+        Config::from_layers(<_>::into_iter([layer]))
+            .map(drop)
+            .map_err(|err| err.root_cause().to_string())
+    }
+
+    /// Finds bindings that are the same, or that hide a longer binding
+    fn duplicate_bindings(&mut self, root: &Value) {
+        let mut keybinds = serde_yaml::from_str::<Value>(Config::default_config())
+            .ok()
+            .and_then(|defaults| defaults.get("keybinds").cloned())
+            .unwrap_or(Value::Null);
+
+        let ours = root.get("keybinds").cloned().unwrap_or(Value::Null);
+        layers::merge(&mut keybinds, ours.clone());
+
+        let sorted = keybinds
+            .as_mapping()
+            .into_iter()
+            .flat_map(|mapping| mapping.iter())
+            .filter_map(|(action, value)| Some((action.as_str()?, value)))
+            .filter(|&(action, _)| action != "preset")
+            // sorted so the report doesn't depend on the order of the file
+            .collect::<BTreeMap<_, _>>();
+
+        let mut bindings = vec![];
+        for (action, value) in sorted {
+            let items = match value {
+                Value::Sequence(items) => items.iter().map(Some).enumerate().collect(),
+                value => vec![(0, Some(value))],
+            };
+
+            for (index, text) in items {
+                let text = match text.and_then(Value::as_str) {
+                    Some(text) => text,
+                    None => continue,
+                };
+
+                let events = match text.parse::<Sequence>().and_then(|seq| seq.events()) {
+                    Ok(events) => events,
+                    // this was already reported
+                    Err(..) => continue,
+                };
+
+                let path = match value {
+                    Value::Sequence(..) => {
+                        vec!["keybinds".into(), action.into(), index.to_string()]
+                    }
+                    _ => vec!["keybinds".into(), action.into()],
+                };
+                bindings.push(Binding {
+                    action: action.to_string(),
+                    text: text.to_string(),
+                    events,
+                    path,
+                    in_file: ours.get(action).is_some(),
+                });
+            }
+        }
+
+        let preset = keybinds
+            .get("preset")
+            .and_then(|preset| serde_yaml::from_value::<Preset>(preset.clone()).ok())
+            .unwrap_or_default();
+        for (action, seq) in preset.sequences() {
+            let action = serde_yaml::to_value(action).ok();
+            bindings.push(Binding {
+                action: action
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                text: seq.to_string(),
+                events: seq.events().expect("valid preset binding"),
+                path: vec!["keybinds".into(), "preset".into()],
+                in_file: ours.get("preset").is_some(),
+            });
+        }
+
+        for (i, binding) in bindings.iter().enumerate() {
+            for other in bindings[..i]
+                .iter()
+                .filter(|other| other.action != binding.action)
+            {
+                let message = if other.events == binding.events {
+                    format!(
+                        "'{}' is bound to both '{}' and '{}'",
+                        binding.text, other.action, binding.action
+                    )
+                } else if binding.events.starts_with(&other.events) {
+                    Self::hidden(other, binding)
+                } else if other.events.starts_with(&binding.events) {
+                    Self::hidden(binding, other)
+                } else {
+                    continue;
+                };
+
+                // point at whichever binding is in this file
+                let path = [binding, other]
+                    .iter()
+                    .find(|binding| binding.in_file)
+                    .map_or(&binding.path, |binding| &binding.path);
+                self.report(path, message);
+            }
+        }
+    }
+
+    fn hidden(short: &Binding, long: &Binding) -> String {
+        format!(
+            "'{}' for '{}' cannot be reached, '{}' is bound to '{}'",
+            long.text, long.action, short.text, short.action
+        )
+    }
+
+    /// Keys that aren't part of the configuration are otherwise silently ignored
+    fn unknown_keys(&mut self, path: &mut Vec<String>, value: &Value, schema: &Value) {
+        let (mapping, schema) = match (value, schema) {
+            (Value::Mapping(mapping), Value::Mapping(schema)) if !schema.is_empty() => {
+                (mapping, schema)
+            }
+            _ => return,
+        };
+
+        for (key, value) in mapping {
+            let name = match key.as_str() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            path.push(name);
+            match schema.get(key) {
+                Some(schema) => self.unknown_keys(path, value, schema),
+                None if !self.has_problem(path) => {
+                    self.report(path, "unknown key".to_string());
+                }
+                None => {}
+            }
+            path.pop();
+        }
+    }
+
+    fn has_problem(&self, path: &[String]) -> bool {
+        let path = path.join(".");
+        self.problems.iter().any(|problem| problem.path == path)
+    }
+
+    fn report(&mut self, path: &[String], message: String) {
+        let (line, column) = self.positions.find(path);
+        self.problems.push(Problem {
+            line,
+            column,
+            path: path.join("."),
+            message,
+        })
+    }
+}
+
+/// Where each key starts in the file
+#[derive(Default)]
+struct Positions {
+    map: HashMap<Vec<String>, (usize, usize)>,
+    path: Vec<String>,
+    frames: Vec<Frame>,
+}
+
+enum Frame {
+    // the key waiting for its value
+    Mapping(Option<String>),
+    Sequence(usize),
+}
+
+impl Positions {
+    fn read(data: &str) -> Self {
+        let mut this = Self::default();
+        // syntax errors were reported before getting here
+        let _ = Parser::new(data.chars()).load(&mut this, false);
+        this
+    }
+
+    /// The position of the path, or of its closest parent that was found
+    fn find(&self, path: &[String]) -> (usize, usize) {
+        (0..=path.len())
+            .rev()
+            .find_map(|len| self.map.get(&path[..len]))
+            .copied()
+            .unwrap_or((1, 1))
+    }
+
+    /// Starts a value, returning whether it belongs to a key or an item that was entered
+    fn enter_value(&mut self, mark: Marker) -> bool {
+        let segment = match self.frames.last_mut() {
+            Some(Frame::Mapping(key)) => match key.take() {
+                Some(key) => key,
+                None => return false,
+            },
+            Some(Frame::Sequence(index)) => {
+                *index += 1;
+                let segment = (*index - 1).to_string();
+                self.path.push(segment);
+                self.mark(mark);
+                return true;
+            }
+            None => return false,
+        };
+        self.path.push(segment);
+        true
+    }
+
+    fn leaf(&mut self, mark: Marker) {
+        if self.enter_value(mark) {
+            self.path.pop();
+        }
+    }
+
+    /// Keeps the earliest position seen for the current path
+    fn mark(&mut self, mark: Marker) {
+        let position = (mark.line(), mark.col() + 1);
+        self.map
+            .entry(self.path.clone())
+            .and_modify(|existing| *existing = position.min(*existing))
+            .or_insert(position);
+    }
+}
+
+impl MarkedEventReceiver for Positions {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(scalar, ..) => {
+                if let Some(Frame::Mapping(key @ None)) = self.frames.last_mut() {
+                    key.replace(scalar.clone());
+                    // a mapping in a list starts at its first key, rather than at its first value
+                    self.mark(mark);
+                    self.path.push(scalar);
+                    self.mark(mark);
+                    self.path.pop();
+                    return;
+                }
+                self.leaf(mark)
+            }
+
+            Event::Alias(..) => self.leaf(mark),
+
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                let entered = self.enter_value(mark);
+                if !entered && !self.frames.is_empty() {
+                    // a complex key isn't something we can point at
+                    self.path.push(String::new());
+                }
+                self.frames.push(match event {
+                    Event::MappingStart(..) => Frame::Mapping(None),
+                    _ => Frame::Sequence(0),
+                });
+            }
+
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.frames.pop();
+                if !self.frames.is_empty() {
+                    self.path.pop();
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// serde_yaml puts the location at the end of its messages, but it is reported separately
+fn strip_location(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(data: &str) -> Vec<String> {
+        check(data).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn default_config_has_no_problems() {
+        assert!(problems(Config::default_config()).is_empty());
+        assert!(problems("").is_empty());
+    }
+
+    #[test]
+    fn bad_hex_color() {
+        assert_eq!(
+            problems("tabs:\n  active:\n    fg: \"#12345\"\n"),
+            ["3:5: tabs.active.fg: invalid hex color: '#12345'"]
+        );
+    }
+
+    #[test]
+    fn unknown_effect() {
+        assert_eq!(
+            problems("tabs:\n  unread:\n    effects: wobbly\n"),
+            ["3:5: tabs.unread.effects: unknown effect: wobbly"]
+        );
+    }
+
+    #[test]
+    fn duplicate_binding() {
+        assert_eq!(
+            problems("keybinds:\n  quit: \"t\"\n"),
+            ["2:3: keybinds.quit: 't' is bound to both 'quit' and 'toggle_timestamp'"]
+        );
+    }
+
+    #[test]
+    fn shadowed_sequence() {
+        assert_eq!(
+            problems("keybinds:\n  preset: vim\n  clear_view: \"g\"\n"),
+            ["2:3: keybinds.preset: 'g g' for 'jump_top' cannot be reached, 'g' is bound to 'clear_view'"]
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert_eq!(
+            problems("timestamps: true\noverlay:\n  enabled: false\n  colour: red\n"),
+            ["4:3: overlay.colour: unknown key"]
+        );
+    }
+
+    #[test]
+    fn wrong_type() {
+        assert_eq!(
+            problems("dedup:\n  window_secs: soon\n"),
+            ["2:3: dedup.window_secs: invalid type: string \"soon\", expected u64"]
+        );
+    }
+
+    #[test]
+    fn reports_each_problem_where_it_is() {
+        let data = "\
+timestamps: true
+tabs:
+  active:
+    fg: \"#FFFFFF\"
+  inactive:
+    bg: \"#GG0000\"
+highlights:
+  keywords:
+    - name: ok
+    - name: [not, a, name]
+";
+        assert_eq!(
+            problems(data),
+            [
+                "6:5: tabs.inactive.bg: invalid hex color: '#GG0000'",
+                "9:7: highlights.keywords.0: missing field `case_sensitive`",
+                "10:7: highlights.keywords.1: invalid type: sequence, expected a string",
+            ]
+        );
+    }
+}
@@ -10,18 +10,19 @@ impl Default for Color {
 }
 
 impl Color {
-    pub(crate) const RED: Self = Self(0xFF, 0x00, 0x00);
-    pub(crate) const WHITE: Self = Self(0xFF, 0xFF, 0xFF);
-    pub(crate) const MAGENTA: Self = Self(0xFF, 0x00, 0xFF);
-    pub(crate) const TEAL: Self = Self(0x00, 0x92, 0x92);
-    pub(crate) const RUSTACEAN_ORANGE: Self = Self(0xf5, 0x7c, 0x00);
-    pub(crate) const TWITCH_PURPLE: Self = Self(0x91, 0x46, 0xff);
+    pub const RED: Self = Self(0xFF, 0x00, 0x00);
+    pub const WHITE: Self = Self(0xFF, 0xFF, 0xFF);
+    pub const MAGENTA: Self = Self(0xFF, 0x00, 0xFF);
+    pub const TEAL: Self = Self(0x00, 0x92, 0x92);
+    pub const RUSTACEAN_ORANGE: Self = Self(0xf5, 0x7c, 0x00);
+    pub const TWITCH_PURPLE: Self = Self(0x91, 0x46, 0xff);
 
-    pub(crate) const fn default_fg() -> Self {
+    pub const fn default_fg() -> Self {
         Self::WHITE
     }
 }
 
+#[cfg(feature = "tui")]
 impl From<Color> for cursive::theme::Style {
     fn from(Color(r, g, b): Color) -> Self {
         let color = cursive::theme::Color::Rgb(r, g, b);
@@ -74,8 +75,8 @@ impl<'de> serde::Deserialize<'de> for Color {
     where
         D: serde::Deserializer<'de>,
     {
-        <std::borrow::Cow<'_, str>>::deserialize(de)?
-            .parse()
-            .map_err(|_| serde::de::Error::custom("invalid hex string"))
+        let s = <std::borrow::Cow<'_, str>>::deserialize(de)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid hex color: '{}'", s)))
     }
 }
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+
+/// A local socket that other programs can use to drive a running instance
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Control {
+    pub enabled: bool,
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl Control {
+    /// The configured path, or `readchat2.sock` in the runtime directory (or the data directory)
+    pub fn socket_path(&self) -> anyhow::Result<PathBuf> {
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
+
+        dirs::runtime_dir()
+            .map(|f| f.join("museun").join("readchat2"))
+            .map(Ok)
+            .unwrap_or_else(super::Config::data_dir)
+            .map(|p| p.join("readchat2.sock"))
+            .with_context(|| anyhow::anyhow!("cannot find a directory for the control socket"))
+    }
+}
@@ -0,0 +1,34 @@
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Dedup {
+    pub enabled: bool,
+    pub window_secs: u64,
+    #[serde(deserialize_with = "ratio")]
+    pub similarity: f32,
+}
+
+impl Default for Dedup {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 30,
+            similarity: 0.9,
+        }
+    }
+}
+
+impl Dedup {
+    pub fn window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.window_secs as _)
+    }
+}
+
+fn ratio<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Deserialize as _, Error as _};
+    match f32::deserialize(deserializer)? {
+        similarity if (0.0..=1.0).contains(&similarity) => Ok(similarity),
+        _ => Err(D::Error::custom("must be between 0.0 and 1.0")),
+    }
+}
@@ -0,0 +1,306 @@
+use serde_yaml::{Mapping, Value};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+use super::layers;
+
+/// Adds `login` to `ignore.users` in the yaml, changing nothing else in it
+///
+/// Only the new entry is written, so the comments and layout of the file are kept
+pub fn add_ignored_user(data: &str, login: &str) -> anyhow::Result<String> {
+    // serde_yaml doesn't read a file that has only comments
+    let blank = data.lines().all(|line| {
+        let line = line.trim_start();
+        line.is_empty() || line.starts_with('#')
+    });
+
+    let root = match serde_yaml::from_str::<Value>(data) {
+        Ok(Value::Null) => Value::Mapping(Mapping::new()),
+        Ok(root) => root,
+        Err(..) if blank => Value::Mapping(Mapping::new()),
+        Err(err) => anyhow::bail!("cannot parse config: {}", err),
+    };
+
+    let mut expected = root.clone();
+    let mut users = root
+        .get("ignore")
+        .and_then(|ignore| ignore.get("users"))
+        .and_then(Value::as_sequence)
+        .cloned()
+        .unwrap_or_default();
+    users.push(login.into());
+    layers::set(&mut expected, &["ignore", "users"], Value::Sequence(users));
+
+    let found = Found::read(data);
+    let edited = match (found.users, found.ignore) {
+        // `users: [a, b]`, the new entry goes before the `]`
+        (Some(users), _) if char_at(data, users.start) == Some('[') => {
+            let end = users
+                .end
+                .map(|mark| byte_offset(data, mark.index()))
+                .ok_or_else(|| anyhow::anyhow!("cannot find the end of ignore.users"))?;
+            let separator = if users.items.is_empty() { "" } else { ", " };
+            insert(data, end, &format!("{}{}", separator, login))
+        }
+        // a block list, the new entry goes on a line after the last one
+        (Some(users), _) => {
+            let last = users
+                .items
+                .last()
+                .ok_or_else(|| anyhow::anyhow!("ignore.users isn't a list of names"))?;
+            let line = format!("{}- {}", " ".repeat(users.start.col()), login);
+            insert_line_after(data, last.line(), &line)
+        }
+        // a block mapping, the list goes on a line before its first key
+        (None, Some(ignore)) => {
+            let line = format!("{}users: [{}]", " ".repeat(ignore.col()), login);
+            insert_line_after(data, ignore.line() - 1, &line)
+        }
+        (None, None) => {
+            let separator = if data.is_empty() || data.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            format!("{}{}\nignore:\n  users: [{}]\n", data, separator, login)
+        }
+    };
+
+    // anything the edit doesn't understand is left for the user, rather than guessed at
+    let same = serde_yaml::from_str::<Value>(&edited)
+        .ok()
+        .and_then(|value| unordered(&value))
+        .zip(unordered(&expected))
+        .is_some_and(|(value, expected)| value == expected);
+    anyhow::ensure!(
+        same,
+        "cannot add '{}' to ignore.users, it has to be added by hand",
+        login
+    );
+    Ok(edited)
+}
+
+/// The value with its mappings compared without their order
+fn unordered(value: &Value) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+fn char_at(data: &str, mark: Marker) -> Option<char> {
+    data.chars().nth(mark.index())
+}
+
+fn byte_offset(data: &str, index: usize) -> usize {
+    data.char_indices()
+        .nth(index)
+        .map_or(data.len(), |(offset, _)| offset)
+}
+
+fn insert(data: &str, offset: usize, text: &str) -> String {
+    let mut data = data.to_string();
+    data.insert_str(offset, text);
+    data
+}
+
+/// Inserts the line after line `after`, counting from 1. `0` puts it first
+fn insert_line_after(data: &str, after: usize, line: &str) -> String {
+    let mut data = data.to_string();
+    let offset = match after.checked_sub(1) {
+        None => 0,
+        Some(index) => match data.match_indices('\n').nth(index) {
+            Some((offset, _)) => offset + 1,
+            // the last line, which might not end with a newline
+            None => {
+                if !data.ends_with('\n') {
+                    data.push('\n')
+                }
+                data.len()
+            }
+        },
+    };
+    data.insert_str(offset, &format!("{}\n", line));
+    data
+}
+
+/// Where `ignore` and `ignore.users` are in the file
+#[derive(Default)]
+struct Found {
+    // the first key of the `ignore` mapping
+    ignore: Option<Marker>,
+    users: Option<List>,
+    path: Vec<String>,
+    frames: Vec<Frame>,
+}
+
+struct List {
+    start: Marker,
+    end: Option<Marker>,
+    items: Vec<Marker>,
+}
+
+enum Frame {
+    // the key waiting for its value
+    Mapping(Option<String>),
+    Sequence,
+}
+
+impl Found {
+    fn read(data: &str) -> Self {
+        let mut this = Self::default();
+        // the yaml was already parsed, so this doesn't fail
+        let _ = Parser::new(data.chars()).load(&mut this, false);
+        this
+    }
+
+    fn at(&self, path: &[&str]) -> bool {
+        self.path
+            .iter()
+            .map(String::as_str)
+            .eq(path.iter().copied())
+    }
+
+    /// Starts a value, returning the key it belongs to
+    fn enter_value(&mut self) -> Option<String> {
+        match self.frames.last_mut()? {
+            Frame::Mapping(key) => key.take(),
+            // items aren't looked into
+            Frame::Sequence => Some(String::new()),
+        }
+    }
+}
+
+impl MarkedEventReceiver for Found {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(scalar, ..) => {
+                if let Some(Frame::Mapping(key @ None)) = self.frames.last_mut() {
+                    key.replace(scalar);
+                    if self.at(&["ignore"]) {
+                        self.ignore.get_or_insert(mark);
+                    }
+                    return;
+                }
+                if matches!(self.frames.last(), Some(Frame::Sequence))
+                    && self.at(&["ignore", "users"])
+                {
+                    if let Some(users) = &mut self.users {
+                        users.items.push(mark)
+                    }
+                }
+                self.enter_value();
+            }
+
+            Event::Alias(..) => {
+                self.enter_value();
+            }
+
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                let segment = self.enter_value();
+                let entered = segment.is_some();
+                if let Some(segment) = segment {
+                    self.path.push(segment);
+                }
+
+                match event {
+                    Event::MappingStart(..) => self.frames.push(Frame::Mapping(None)),
+                    _ => {
+                        if entered && self.at(&["ignore", "users"]) {
+                            self.users.get_or_insert(List {
+                                start: mark,
+                                end: None,
+                                items: vec![],
+                            });
+                        }
+                        self.frames.push(Frame::Sequence)
+                    }
+                }
+
+                if !entered && self.frames.len() > 1 {
+                    // a complex key, or the root
+                    self.path.push(String::new());
+                }
+            }
+
+            Event::MappingEnd | Event::SequenceEnd => {
+                if matches!(event, Event::SequenceEnd) && self.at(&["ignore", "users"]) {
+                    if let Some(users) = &mut self.users {
+                        users.end.get_or_insert(mark);
+                    }
+                }
+                self.frames.pop();
+                if !self.frames.is_empty() {
+                    self.path.pop();
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check(before: &str, after: &str) {
+        assert_eq!(add_ignored_user(before, "nightbot").unwrap(), after);
+    }
+
+    #[test]
+    fn adds_to_a_flow_list() {
+        check(
+            "# a comment\nignore:\n  # the users\n  users: []\n  mode: hide\n",
+            "# a comment\nignore:\n  # the users\n  users: [nightbot]\n  mode: hide\n",
+        );
+        check(
+            "ignore:\n  users: [spammer] # bots\n",
+            "ignore:\n  users: [spammer, nightbot] # bots\n",
+        );
+    }
+
+    #[test]
+    fn adds_to_a_block_list() {
+        check(
+            "ignore:\n  users:\n    - spammer # bots\n  mode: hide\n",
+            "ignore:\n  users:\n    - spammer # bots\n    - nightbot\n  mode: hide\n",
+        );
+        check(
+            "ignore:\n  users:\n  - spammer\n",
+            "ignore:\n  users:\n  - spammer\n  - nightbot\n",
+        );
+        check(
+            "ignore:\n  users:\n    - spammer",
+            "ignore:\n  users:\n    - spammer\n    - nightbot\n",
+        );
+    }
+
+    #[test]
+    fn adds_the_list() {
+        check(
+            "timestamps: true\nignore:\n    mode: hide\n",
+            "timestamps: true\nignore:\n    users: [nightbot]\n    mode: hide\n",
+        );
+        check(
+            "timestamps: true # comment",
+            "timestamps: true # comment\n\nignore:\n  users: [nightbot]\n",
+        );
+        check("", "\nignore:\n  users: [nightbot]\n");
+        check(
+            "# nothing yet\n",
+            "# nothing yet\n\nignore:\n  users: [nightbot]\n",
+        );
+    }
+
+    #[test]
+    fn leaves_what_it_does_not_understand() {
+        for data in [
+            "ignore:\n  users: ~\n",
+            "ignore: {mode: hide}\n",
+            "ignore:\n  users: spammer\n",
+        ] {
+            assert!(add_ignored_user(data, "nightbot").is_err(), "{}", data);
+        }
+    }
+}
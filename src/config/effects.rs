@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 
+#[cfg(feature = "tui")]
 use cursive::theme::Effect;
 
 macro_rules! make_effects {
@@ -26,7 +27,7 @@ macro_rules! make_effects {
                 }
             )*
 
-            pub fn into_iter(self) -> impl Iterator<Item = (&'static str, bool)> + DoubleEndedIterator + ExactSizeIterator {
+            pub fn flags(self) -> impl DoubleEndedIterator<Item = (&'static str, bool)> + ExactSizeIterator {
                 <_>::into_iter([$(stringify!($id),)*]).zip([$(self.$id,)*])
             }
         }
@@ -43,6 +44,7 @@ make_effects! {
     blink
 }
 
+#[cfg(feature = "tui")]
 impl std::ops::BitOr<Effect> for Effects {
     type Output = Self;
 
@@ -83,7 +85,7 @@ impl<'de> serde::Deserialize<'de> for Effects {
                 D: serde::Deserializer<'de>,
             {
                 use serde::de::Deserialize as _;
-                self.visit_str(&*<Cow<'_, str>>::deserialize(de)?)
+                self.visit_str(&<Cow<'_, str>>::deserialize(de)?)
             }
 
             fn visit_none<E>(self) -> Result<Self::Value, E>
@@ -93,6 +95,13 @@ impl<'de> serde::Deserialize<'de> for Effects {
                 Ok(Self::Value::default())
             }
 
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Self::Value::default())
+            }
+
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
@@ -110,7 +119,7 @@ impl<'de> serde::Deserialize<'de> for Effects {
                             "blink" => &mut eff.blink,
                             // XXX: a `serde_yaml` hack because its not figuring out `Some|None|str`
                             "~" => return Ok(eff),
-                            e => return Err(E::custom(&format!("unknown effect: {}", e))),
+                            e => return Err(E::custom(format!("unknown effect: {}", e))),
                         } = true;
                         Ok(eff)
                     },
@@ -118,7 +127,8 @@ impl<'de> serde::Deserialize<'de> for Effects {
             }
         }
 
-        deserializer.deserialize_str(V)
+        // `~` is a unit rather than a string when read from a `serde_yaml::Value`
+        deserializer.deserialize_any(V)
     }
 }
 
@@ -127,18 +137,19 @@ impl serde::Serialize for Effects {
     where
         S: serde::Serializer,
     {
-        let s =
-            self.into_iter()
-                .filter_map(|(k, v)| v.then(|| k))
-                .fold(String::new(), |mut a, c| {
-                    if !a.is_empty() {
-                        a.push(' ');
-                        a.push(SEPERATOR);
-                        a.push(' ');
-                    }
-                    a.push_str(c.as_ref());
-                    a
-                });
+        let s = self
+            .flags()
+            .filter(|&(_, v)| v)
+            .map(|(k, _)| k)
+            .fold(String::new(), |mut a, c| {
+                if !a.is_empty() {
+                    a.push(' ');
+                    a.push(SEPERATOR);
+                    a.push(' ');
+                }
+                a.push_str(c.as_ref());
+                a
+            });
 
         if s.is_empty() {
             serializer.serialize_none()
@@ -148,11 +159,12 @@ impl serde::Serialize for Effects {
     }
 }
 
+#[cfg(feature = "tui")]
 impl From<Effects> for cursive::theme::Style {
     fn from(effects: Effects) -> Self {
         Self {
             effects: effects
-                .into_iter()
+                .flags()
                 .zip([
                     Effect::Simple,
                     Effect::Reverse,
@@ -162,9 +174,16 @@ impl From<Effects> for cursive::theme::Style {
                     Effect::Underline,
                     Effect::Blink,
                 ])
-                .fold(Default::default(), |eff, ((_, v), e)| {
-                    v.then(|| eff | e).unwrap_or_else(|| eff & e)
-                }),
+                .fold(
+                    Default::default(),
+                    |eff, ((_, v), e)| {
+                        if v {
+                            eff | e
+                        } else {
+                            eff & e
+                        }
+                    },
+                ),
             ..Default::default()
         }
     }
@@ -1,9 +1,15 @@
-use super::{Color, Effects, Style};
+use super::{Color, Effects, Rule, Style};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Highlights {
     pub mention: Style,
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub replies: bool,
     pub keywords: Vec<Keyword>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
 }
 
 impl Default for Highlights {
@@ -21,11 +27,25 @@ impl Default for Highlights {
 
         Self {
             mention: Style::fg(Color::RED).with_effects(Effects::bold()),
+            names: Vec::new(),
+            replies: false,
             keywords,
+            rules: Vec::new(),
         }
     }
 }
 
+impl Highlights {
+    pub fn mention_names<'a>(&'a self, our_name: Option<&'a str>) -> Vec<&'a str> {
+        self.names
+            .iter()
+            .map(String::as_str)
+            .chain(our_name)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Keyword {
     pub name: String,
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+/// A long-lived program that is sent every message, and can reply with directives for it
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Hook {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "Hook::default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Hook {
+    const fn default_timeout_ms() -> u64 {
+        200
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
@@ -0,0 +1,55 @@
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Ignore {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<Pattern>,
+    #[serde(default)]
+    pub mode: IgnoreMode,
+    #[serde(default)]
+    pub persist: bool,
+}
+
+impl Ignore {
+    pub fn contains_user(&self, login: &str) -> bool {
+        self.users
+            .iter()
+            .any(|user| user.eq_ignore_ascii_case(login))
+    }
+
+    pub fn matches(&self, login: &str, data: &str) -> bool {
+        self.contains_user(login) || self.patterns.iter().any(|p| p.0.is_match(data))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreMode {
+    #[default]
+    Hide,
+    Collapse,
+}
+
+#[derive(Clone, Debug)]
+pub struct Pattern(pub regex::Regex);
+
+impl serde::Serialize for Pattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Pattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <std::borrow::Cow<'_, str>>::deserialize(deserializer)?
+            .parse()
+            .map(Self)
+            .map_err(<D::Error as serde::de::Error>::custom)
+    }
+}
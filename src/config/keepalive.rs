@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+/// How the connection is kept alive while the chat is quiet
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Keepalive {
+    #[serde(deserialize_with = "non_zero")]
+    pub ping_interval_secs: u64,
+    #[serde(deserialize_with = "non_zero")]
+    pub timeout_secs: u64,
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            ping_interval_secs: 15,
+            timeout_secs: 15,
+        }
+    }
+}
+
+impl Keepalive {
+    /// How long the connection can be quiet before we send a PING
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.ping_interval_secs)
+    }
+
+    /// How long to wait for anything after a PING, before giving up on the connection
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+// zero would either ping on every wakeup, or give up right after the first ping
+fn non_zero<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Deserialize as _, Error as _};
+    match u64::deserialize(deserializer)? {
+        0 => Err(D::Error::custom("must be at least 1 second")),
+        secs => Ok(secs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_zero_durations() {
+        for yaml in [
+            "{ping_interval_secs: 0, timeout_secs: 15}",
+            "{ping_interval_secs: 15, timeout_secs: 0}",
+        ] {
+            let err = serde_yaml::from_str::<Keepalive>(yaml).unwrap_err();
+            assert!(
+                err.to_string().contains("must be at least 1 second"),
+                "{}",
+                yaml
+            );
+        }
+
+        let keepalive =
+            serde_yaml::from_str::<Keepalive>("{ping_interval_secs: 1, timeout_secs: 1}").unwrap();
+        assert_eq!(keepalive.timeout(), Duration::from_secs(1));
+    }
+}
@@ -1,43 +1,253 @@
 use std::{borrow::Cow, collections::BTreeMap};
 
-use anyhow::Context;
+#[cfg(feature = "tui")]
+mod events;
+#[cfg(feature = "tui")]
+pub use events::Matcher;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct KeyBinds {
+    #[serde(default)]
+    pub preset: Preset,
     #[serde(flatten)]
-    pub map: BTreeMap<Action, Input>,
+    pub map: BTreeMap<Action, Bindings>,
+}
+
+impl KeyBinds {
+    /// Every binding, including the ones added by the preset
+    pub fn sequences(&self) -> Vec<(Action, Sequence)> {
+        let bindings = self.map.iter().flat_map(|(action, bindings)| {
+            bindings.0.iter().map(move |seq| (*action, seq.clone()))
+        });
+        bindings.chain(self.preset.sequences()).collect()
+    }
 }
 
 impl Default for KeyBinds {
     fn default() -> Self {
         Self {
+            preset: Preset::default(),
             map: <_>::into_iter([
-                (Action::FocusStatusView, Input::char('0')),
-                (Action::FocusMessagesView, Input::char('1')),
-                (Action::FocusLinksView, Input::char('2')),
-                (Action::FocusHighlightsView, Input::char('3')),
-                (Action::Quit, Input::char('q')),
-                (Action::ToggleTimestamp, Input::char('t')),
-                (Action::ToggleBadges, Input::char('b')),
+                (Action::FocusStatusView, Bindings::char('0')),
+                (Action::FocusMessagesView, Bindings::char('1')),
+                (Action::FocusLinksView, Bindings::char('2')),
+                (Action::FocusHighlightsView, Bindings::char('3')),
+                (Action::FocusStatsView, Bindings::char('4')),
+                (Action::Quit, Bindings::char('q')),
+                (Action::ToggleTimestamp, Bindings::char('t')),
+                (Action::ToggleBadges, Bindings::char('b')),
+                (Action::IgnoreSelected, Bindings::char('i')),
+                (Action::TogglePoll, Bindings::char('p')),
+                (Action::OpenPrompt, Bindings::char(':')),
+                (Action::ReloadConfig, Bindings::char('r')),
+                (Action::ScrollUp, Bindings::parse(&["up"])),
+                (Action::ScrollDown, Bindings::parse(&["down"])),
+                (Action::PageUp, Bindings::parse(&["pgup"])),
+                (Action::PageDown, Bindings::parse(&["pgdown"])),
+                (Action::JumpTop, Bindings::parse(&["home"])),
+                (Action::JumpBottom, Bindings::parse(&["end"])),
+                (Action::NextTab, Bindings::parse(&["tab"])),
+                (Action::PrevTab, Bindings::parse(&["shift-tab"])),
+                (Action::ClearView, Bindings::parse(&["ctrl-l"])),
+                (Action::ToggleHelp, Bindings::char('?')),
             ])
             .collect(),
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Action {
     FocusStatusView,
     FocusMessagesView,
     FocusLinksView,
     FocusHighlightsView,
+    FocusStatsView,
 
     Quit,
     ToggleTimestamp,
     ToggleBadges,
+    IgnoreSelected,
+    TogglePoll,
+    OpenPrompt,
+    ReloadConfig,
+
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    JumpTop,
+    JumpBottom,
+    NextTab,
+    PrevTab,
+    ClearView,
+
+    ToggleHelp,
+}
+
+/// Bindings that are added to the configured ones
+#[derive(Copy, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    #[default]
+    Default,
+    Vim,
+}
+
+impl Preset {
+    const VIM: &'static [(Action, &'static str)] = &[
+        (Action::ScrollDown, "j"),
+        (Action::ScrollUp, "k"),
+        (Action::PageDown, "ctrl-d"),
+        (Action::PageUp, "ctrl-u"),
+        (Action::JumpTop, "g g"),
+        (Action::JumpBottom, "G"),
+    ];
+
+    pub fn sequences(&self) -> impl Iterator<Item = (Action, Sequence)> {
+        let bindings = match self {
+            Self::Default => &[],
+            Self::Vim => Self::VIM,
+        };
+        bindings
+            .iter()
+            .map(|(action, seq)| (*action, seq.parse().expect("valid preset binding")))
+    }
+}
+
+/// Every sequence bound to an action
+///
+/// This is written as a single sequence, or as a list of them
+#[derive(Clone, Debug, Default)]
+pub struct Bindings(pub Vec<Sequence>);
+
+impl Bindings {
+    fn char(ch: char) -> Self {
+        Self(vec![Sequence(vec![Input::char(ch)])])
+    }
+
+    fn parse(sequences: &[&str]) -> Self {
+        let sequences = sequences
+            .iter()
+            .map(|seq| seq.parse().expect("valid default binding"));
+        Self(sequences.collect())
+    }
+
+    /// The binding shown for the action, e.g. in the tab bar
+    pub fn first(&self) -> Option<&Sequence> {
+        self.0.first()
+    }
+}
+
+impl serde::Serialize for Bindings {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0.as_slice() {
+            [sequence] => sequence.serialize(serializer),
+            sequences => sequences.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Bindings {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Bindings;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a keybind or a list of keybinds")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Sequence::parse(v).map(|sequence| Bindings(vec![sequence]))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut sequences = vec![];
+                while let Some(sequence) = seq.next_element()? {
+                    sequences.push(sequence)
+                }
+                Ok(Bindings(sequences))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Keys pressed one after another, e.g. `g g` or `ctrl-x t`
+#[derive(Clone, Debug)]
+pub struct Sequence(pub Vec<Input>);
+
+impl Sequence {
+    fn parse<E>(s: &str) -> Result<Self, E>
+    where
+        E: serde::de::Error,
+    {
+        s.parse()
+            .map_err(|err| E::custom(format!("invalid keybind '{}': {:#}", s, err)))
+    }
+}
+
+impl std::fmt::Display for Sequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, input) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", input)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Sequence {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> anyhow::Result<Self> {
+        let keys = input
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        anyhow::ensure!(!keys.is_empty(), "a key is needed");
+        Ok(Self(keys))
+    }
+}
+
+impl serde::Serialize for Sequence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Sequence {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <Cow<'_, str>>::deserialize(deserializer)?;
+        Self::parse(&s)
+    }
 }
 
+/// A single key, with its modifiers
 #[derive(Copy, Clone, Debug)]
 pub struct Input {
     pub key: Key,
@@ -49,50 +259,18 @@ pub struct Input {
 impl std::fmt::Display for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         <_>::into_iter([
-            (self.ctrl, 'c'), //
-            (self.alt, 'a'),
-            (self.shift, 's'),
+            (self.ctrl, "ctrl-"), //
+            (self.alt, "alt-"),
+            (self.shift, "shift-"),
         ])
-        .filter_map(|(k, v)| k.then(|| [v, '-']))
-        .flatten()
-        .try_for_each(|ch| write!(f, "{}", ch))?;
+        .filter(|&(k, _)| k)
+        .try_for_each(|(_, v)| f.write_str(v))?;
 
         match self.key {
             Key::Char(ch) => write!(f, "{}", ch),
-            Key::F(n) => {
-                let head = std::iter::once('f');
-                let tail = split_digits(n).map(|c| (c + b'0') as char);
-                head.chain(tail).try_for_each(|ch| write!(f, "{}", ch))
-            }
-        }
-    }
-}
-
-impl From<Input> for cursive::event::Event {
-    fn from(input: Input) -> Self {
-        if let Some(fkey) = input.key.maybe_function_key() {
-            return match (input.ctrl, input.alt, input.shift) {
-                (true, true, true) => unreachable!("this is an invalid combination"), // TODO: error
-                (true, true, false) => Self::CtrlAlt(fkey),
-                (true, false, true) => Self::CtrlShift(fkey),
-                (true, false, false) => Self::Ctrl(fkey),
-                (false, true, true) => Self::AltShift(fkey),
-                (false, true, false) => Self::Alt(fkey),
-                (false, false, true) => Self::Shift(fkey),
-                (false, false, false) => Self::Key(fkey),
-            };
-        };
-
-        if let Key::Char(ch) = input.key {
-            return match (input.ctrl, input.alt) {
-                (true, true) => unreachable!("this is an invalid combination"),
-                (true, false) => Self::CtrlChar(ch),
-                (false, true) => Self::AltChar(ch),
-                (false, false) => Self::Char(ch),
-            };
+            Key::F(n) => write!(f, "f{}", n),
+            key => f.write_str(key.name().expect("named key")),
         }
-
-        unreachable!("a key should have been processed")
     }
 }
 
@@ -105,55 +283,102 @@ impl Input {
             shift: false,
         }
     }
+
+    /// Checks that the modifiers can be combined for the key
+    fn check(&self) -> anyhow::Result<()> {
+        match self.key {
+            Key::Char(ch) => anyhow::ensure!(
+                !(self.ctrl && self.alt),
+                "ctrl and alt cannot be combined for '{}'",
+                ch
+            ),
+            Key::Space => anyhow::ensure!(
+                !(self.ctrl && self.alt),
+                "ctrl and alt cannot be combined for ' '"
+            ),
+            _ => anyhow::ensure!(
+                !(self.ctrl && self.alt && self.shift),
+                "ctrl, alt and shift cannot all be combined"
+            ),
+        }
+        Ok(())
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub enum Key {
     Char(char),
     F(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Tab,
+    Enter,
+    Esc,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
 }
 
 impl Key {
-    fn maybe_function_key(&self) -> Option<cursive::event::Key> {
-        use cursive::event::Key as K;
-        Some(match self {
-            Self::Char(_) => return None,
-            Self::F(0) => K::F0,
-            Self::F(1) => K::F1,
-            Self::F(2) => K::F2,
-            Self::F(3) => K::F3,
-            Self::F(4) => K::F4,
-            Self::F(5) => K::F5,
-            Self::F(6) => K::F6,
-            Self::F(7) => K::F7,
-            Self::F(8) => K::F8,
-            Self::F(9) => K::F9,
-            Self::F(10) => K::F10,
-            Self::F(11) => K::F11,
-            Self::F(12) => K::F12,
-            _ => unreachable!(),
-        })
-    }
-}
-
-impl serde::Serialize for Input {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let s = self.to_string();
-        serializer.serialize_str(&s)
+    /// The names of the keys, the first name of each is the one that is written
+    const NAMES: &'static [(&'static [&'static str], Self)] = &[
+        (&["up"], Self::Up),
+        (&["down"], Self::Down),
+        (&["left"], Self::Left),
+        (&["right"], Self::Right),
+        (&["pgup", "pageup"], Self::PageUp),
+        (&["pgdown", "pagedown"], Self::PageDown),
+        (&["home"], Self::Home),
+        (&["end"], Self::End),
+        (&["tab"], Self::Tab),
+        (&["enter", "return"], Self::Enter),
+        (&["esc", "escape"], Self::Esc),
+        (&["space"], Self::Space),
+        (&["backspace"], Self::Backspace),
+        (&["del", "delete"], Self::Delete),
+        (&["ins", "insert"], Self::Insert),
+    ];
+
+    fn name(&self) -> Option<&'static str> {
+        Self::NAMES
+            .iter()
+            .find(|(_, key)| std::mem::discriminant(key) == std::mem::discriminant(self))
+            .map(|(names, _)| names[0])
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(names, _)| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+            .map(|&(_, key)| key)
     }
 }
 
-impl<'de> serde::Deserialize<'de> for Input {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        <Cow<'_, str>>::deserialize(deserializer)?
-            .parse()
-            .map_err(|err| <D::Error as serde::de::Error>::custom(err))
+impl std::str::FromStr for Key {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> anyhow::Result<Self> {
+        let mut chars = input.chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            anyhow::ensure!(!ch.is_whitespace(), "use 'space' for the space key");
+            return Ok(Self::Char(ch));
+        }
+
+        if let Some(key) = Self::from_name(input) {
+            return Ok(key);
+        }
+
+        match input.strip_prefix(&['f', 'F'][..]).map(str::parse) {
+            Some(Ok(n @ 0..=12)) => Ok(Self::F(n)),
+            Some(Ok(..)) => anyhow::bail!("only f0-f12 is supported"),
+            _ => anyhow::bail!("unknown key '{}'", input),
+        }
     }
 }
 
@@ -161,79 +386,119 @@ impl std::str::FromStr for Input {
     type Err = anyhow::Error;
     fn from_str(input: &str) -> anyhow::Result<Self> {
         let input = input.trim();
-        let (mut ctrl, mut alt, mut shift) = <_>::default();
-        let mut out = None;
-
-        for ch in input.split_terminator('-') {
-            anyhow::ensure!(out.is_none(), "character was already found");
-            match ch.as_bytes() {
-                [b'c'] => ctrl = true,
-                [b'a'] => alt = true,
-                [b's'] => shift = true,
-
-                [b'f', n @ ..] => match std::str::from_utf8(n)?.parse()? {
-                    n @ 0..=12 => out = Some(Key::F(n)),
-                    _ => anyhow::bail!("only f0-f12 is supported"),
-                },
-                [ch] if ch.is_ascii_alphanumeric() || ch.is_ascii_punctuation() => {
-                    if ch.is_ascii_uppercase() {
-                        shift = true;
-                    }
-                    out = Some(Key::Char((*ch as char).to_ascii_lowercase()))
-                }
 
-                ch => anyhow::bail!("unsupported input: {}", std::str::from_utf8(ch)?),
-            };
-        }
+        // '-' is a key as well, so 'ctrl--' is 'ctrl' with '-'
+        let (modifiers, key) = match input.strip_suffix("--") {
+            Some(modifiers) => (modifiers, "-"),
+            None => match input.rsplit_once('-') {
+                Some((modifiers, key)) if !modifiers.is_empty() => (modifiers, key),
+                _ => ("", input),
+            },
+        };
+        anyhow::ensure!(!key.is_empty(), "a key is needed after the modifiers");
 
-        if out.is_none() {
-            match (ctrl, alt, shift) {
-                (true, _, _) => out = Some(Key::Char('c')),
-                (_, true, _) => out = Some(Key::Char('a')),
-                (_, _, true) => out = Some(Key::Char('s')),
-                _ => {}
+        let (mut ctrl, mut alt, mut shift) = <_>::default();
+        for modifier in modifiers.split_terminator('-') {
+            match modifier {
+                "c" | "ctrl" => ctrl = true,
+                "a" | "alt" => alt = true,
+                "s" | "shift" => shift = true,
+                _ => anyhow::bail!("unknown modifier '{}'", modifier),
             }
         }
 
-        let mut key = out.with_context(|| "character sequence must be present")?;
-        if matches!(key, Key::Char(_)) && (ctrl && alt) {
-            anyhow::bail!("ctrl cannot be used with alt for character keys")
-        }
-
-        if matches!(key, Key::F(..)) && (ctrl && alt && shift) {
-            anyhow::bail!("ctrl cannot be used with alt and shift for function keys")
-        }
+        let mut key = key.parse()?;
 
-        if shift {
-            if let Key::Char(ch) = &mut key {
-                *ch = ch.to_ascii_uppercase()
+        // letters are shifted by their case
+        if let Key::Char(ch) = &mut key {
+            if shift {
+                anyhow::ensure!(
+                    ch.is_ascii_alphabetic(),
+                    "shift cannot be used with '{}', use the shifted character instead",
+                    ch
+                );
+                *ch = ch.to_ascii_uppercase();
+                shift = false;
             }
         }
 
-        Ok(Self {
+        let input = Self {
             key,
             ctrl,
             alt,
             shift,
-        })
+        };
+        input.check()?;
+        Ok(input)
     }
 }
 
-fn split_digits(mut n: u8) -> impl Iterator<Item = u8> {
-    let y = 0_u8.count_zeros() - n.leading_zeros();
-    let x = ((y + 1) * 1233) >> 12;
-    let mut mag = 10_u8.pow(x);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if n < mag {
-        mag /= 10;
+    fn parse(input: &str) -> String {
+        input.parse::<Input>().unwrap().to_string()
     }
 
-    std::iter::from_fn(move || {
-        (mag > 0).then(|| {
-            let d = n / mag;
-            n %= mag;
-            mag /= 10;
-            d
-        })
-    })
+    fn parse_err(input: &str) -> String {
+        input.parse::<Input>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn named_keys() {
+        assert_eq!(parse("up"), "up");
+        assert_eq!(parse("PageUp"), "pgup");
+        assert_eq!(parse("escape"), "esc");
+        assert_eq!(parse("return"), "enter");
+        assert_eq!(parse("f12"), "f12");
+        assert_eq!(parse("space"), "space");
+        assert_eq!(parse("-"), "-");
+
+        assert_eq!(parse_err("f13"), "only f0-f12 is supported");
+        assert_eq!(parse_err("nope"), "unknown key 'nope'");
+    }
+
+    #[test]
+    fn modifiers() {
+        assert_eq!(parse("ctrl-l"), "ctrl-l");
+        assert_eq!(parse("c-a-up"), "ctrl-alt-up");
+        assert_eq!(parse("shift-tab"), "shift-tab");
+        assert_eq!(parse("shift-g"), "G");
+        assert_eq!(parse("ctrl--"), "ctrl--");
+    }
+
+    #[test]
+    fn bad_modifiers() {
+        assert_eq!(parse_err("meta-x"), "unknown modifier 'meta'");
+        assert_eq!(parse_err("ctrl-"), "a key is needed after the modifiers");
+        assert_eq!(
+            parse_err("ctrl-alt-x"),
+            "ctrl and alt cannot be combined for 'x'"
+        );
+        assert_eq!(
+            parse_err("ctrl-alt-shift-up"),
+            "ctrl, alt and shift cannot all be combined"
+        );
+        assert_eq!(
+            parse_err("shift-1"),
+            "shift cannot be used with '1', use the shifted character instead"
+        );
+    }
+
+    #[test]
+    fn sequences() {
+        let seq = "g  shift-g ctrl-l".parse::<Sequence>().unwrap();
+        assert_eq!(seq.0.len(), 3);
+        assert_eq!(seq.to_string(), "g G ctrl-l");
+
+        assert_eq!(
+            "  ".parse::<Sequence>().unwrap_err().to_string(),
+            "a key is needed"
+        );
+        assert_eq!(
+            "g nope".parse::<Sequence>().unwrap_err().to_string(),
+            "unknown key 'nope'"
+        );
+    }
 }
@@ -0,0 +1,266 @@
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use cursive::event::Event;
+
+use super::{Action, Input, Key, KeyBinds, Sequence};
+
+impl Sequence {
+    /// The events for each key, in order
+    pub fn events(&self) -> anyhow::Result<Vec<Event>> {
+        self.0.iter().copied().map(Event::try_from).collect()
+    }
+}
+
+/// Matches the keys against the bindings as they are pressed
+///
+/// When a binding is also the start of a longer one, the shorter one wins.
+/// A sequence is dropped when its next key isn't pressed within a second
+pub struct Matcher {
+    sequences: Vec<(Vec<Event>, Action)>,
+    pending: Vec<Event>,
+    last: Option<Instant>,
+}
+
+impl Matcher {
+    /// How long the next key of a binding is waited for
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn new(keybinds: &KeyBinds) -> Self {
+        let sequences = keybinds
+            .sequences()
+            .into_iter()
+            // these were rejected when the configuration was loaded
+            .filter_map(|(action, seq)| Some((seq.events().ok()?, action)))
+            .collect();
+
+        Self {
+            sequences,
+            pending: vec![],
+            last: None,
+        }
+    }
+
+    /// Every key used by a binding
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = vec![];
+        for event in self.sequences.iter().flat_map(|(seq, _)| seq) {
+            if !events.contains(event) {
+                events.push(event.clone())
+            }
+        }
+        events
+    }
+
+    /// Adds a key, returning the action once a binding is complete
+    pub fn feed(&mut self, event: Event) -> Option<Action> {
+        self.feed_at(event, Instant::now())
+    }
+
+    fn feed_at(&mut self, event: Event, now: Instant) -> Option<Action> {
+        // keys that aren't bound are never fed, so a binding that was
+        // started and then left is dropped once it has waited too long
+        if self
+            .last
+            .replace(now)
+            .is_some_and(|last| now.duration_since(last) > Self::TIMEOUT)
+        {
+            self.pending.clear();
+        }
+
+        self.pending.push(event.clone());
+
+        loop {
+            if let Some((_, action)) = self.sequences.iter().find(|(seq, _)| *seq == self.pending) {
+                self.pending.clear();
+                return Some(*action);
+            }
+
+            if self
+                .sequences
+                .iter()
+                .any(|(seq, _)| seq.starts_with(&self.pending))
+            {
+                return None;
+            }
+
+            // nothing continues like this, so this key could start a new binding
+            if self.pending.len() == 1 {
+                self.pending.clear();
+                return None;
+            }
+            self.pending = vec![event.clone()];
+        }
+    }
+}
+
+impl TryFrom<Input> for Event {
+    type Error = anyhow::Error;
+
+    fn try_from(input: Input) -> anyhow::Result<Self> {
+        input.check()?;
+
+        let key = match input.key.as_cursive_key() {
+            Some(key) => key,
+            None => {
+                let ch = match input.key {
+                    Key::Space => ' ',
+                    Key::Char(ch) => ch,
+                    _ => unreachable!("every other key is a cursive key"),
+                };
+                return Ok(match (input.ctrl, input.alt) {
+                    (true, _) => Self::CtrlChar(ch),
+                    (false, true) => Self::AltChar(ch),
+                    (false, false) => Self::Char(ch),
+                });
+            }
+        };
+
+        Ok(match (input.ctrl, input.alt, input.shift) {
+            (true, true, _) => Self::CtrlAlt(key),
+            (true, false, true) => Self::CtrlShift(key),
+            (true, false, false) => Self::Ctrl(key),
+            (false, true, true) => Self::AltShift(key),
+            (false, true, false) => Self::Alt(key),
+            (false, false, true) => Self::Shift(key),
+            (false, false, false) => Self::Key(key),
+        })
+    }
+}
+
+impl Key {
+    fn as_cursive_key(&self) -> Option<cursive::event::Key> {
+        use cursive::event::Key as K;
+        Some(match self {
+            Self::Char(_) | Self::Space => return None,
+            Self::F(0) => K::F0,
+            Self::F(1) => K::F1,
+            Self::F(2) => K::F2,
+            Self::F(3) => K::F3,
+            Self::F(4) => K::F4,
+            Self::F(5) => K::F5,
+            Self::F(6) => K::F6,
+            Self::F(7) => K::F7,
+            Self::F(8) => K::F8,
+            Self::F(9) => K::F9,
+            Self::F(10) => K::F10,
+            Self::F(11) => K::F11,
+            Self::F(12) => K::F12,
+            Self::F(_) => unreachable!("only f0-f12 are parsed"),
+            Self::Up => K::Up,
+            Self::Down => K::Down,
+            Self::Left => K::Left,
+            Self::Right => K::Right,
+            Self::PageUp => K::PageUp,
+            Self::PageDown => K::PageDown,
+            Self::Home => K::Home,
+            Self::End => K::End,
+            Self::Tab => K::Tab,
+            Self::Enter => K::Enter,
+            Self::Esc => K::Esc,
+            Self::Backspace => K::Backspace,
+            Self::Delete => K::Del,
+            Self::Insert => K::Ins,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::keybinds::{Bindings, Preset};
+
+    fn matcher(bindings: &[(Action, &str)]) -> Matcher {
+        let keybinds = KeyBinds {
+            preset: Preset::default(),
+            map: bindings
+                .iter()
+                .map(|&(action, seq)| (action, Bindings::parse(&[seq])))
+                .collect(),
+        };
+        Matcher::new(&keybinds)
+    }
+
+    #[test]
+    fn completes_a_sequence() {
+        let mut matcher = matcher(&[(Action::JumpTop, "g g"), (Action::Quit, "q")]);
+        let now = Instant::now();
+        assert_eq!(matcher.feed_at(Event::Char('g'), now), None);
+        assert_eq!(
+            matcher.feed_at(Event::Char('g'), now),
+            Some(Action::JumpTop)
+        );
+        assert_eq!(matcher.feed_at(Event::Char('q'), now), Some(Action::Quit));
+    }
+
+    #[test]
+    fn shorter_binding_wins() {
+        let mut matcher = matcher(&[(Action::JumpTop, "g g"), (Action::ClearView, "g")]);
+        let now = Instant::now();
+        assert_eq!(
+            matcher.feed_at(Event::Char('g'), now),
+            Some(Action::ClearView)
+        );
+        assert_eq!(
+            matcher.feed_at(Event::Char('g'), now),
+            Some(Action::ClearView)
+        );
+    }
+
+    #[test]
+    fn restarts_on_a_key_that_does_not_continue() {
+        let mut matcher = matcher(&[(Action::JumpTop, "g g"), (Action::Quit, "q")]);
+        let now = Instant::now();
+        assert_eq!(matcher.feed_at(Event::Char('g'), now), None);
+        assert_eq!(matcher.feed_at(Event::Char('q'), now), Some(Action::Quit));
+        assert_eq!(matcher.feed_at(Event::Char('g'), now), None);
+        assert_eq!(
+            matcher.feed_at(Event::Char('g'), now),
+            Some(Action::JumpTop)
+        );
+    }
+
+    #[test]
+    fn drops_a_sequence_after_the_timeout() {
+        let mut matcher = matcher(&[(Action::JumpTop, "g g")]);
+        let now = Instant::now();
+        assert_eq!(matcher.feed_at(Event::Char('g'), now), None);
+
+        let later = now + Matcher::TIMEOUT * 2;
+        assert_eq!(matcher.feed_at(Event::Char('g'), later), None);
+        assert_eq!(
+            matcher.feed_at(Event::Char('g'), later + Matcher::TIMEOUT / 2),
+            Some(Action::JumpTop)
+        );
+    }
+
+    #[test]
+    fn input_events() {
+        let events = [
+            "g",
+            "G",
+            "ctrl-l",
+            "alt-x",
+            "shift-tab",
+            "ctrl-alt-up",
+            "space",
+        ]
+        .iter()
+        .map(|input| Event::try_from(input.parse::<Input>().unwrap()).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                Event::Char('g'),
+                Event::Char('G'),
+                Event::CtrlChar('l'),
+                Event::AltChar('x'),
+                Event::Shift(cursive::event::Key::Tab),
+                Event::CtrlAlt(cursive::event::Key::Up),
+                Event::Char(' '),
+            ]
+        );
+    }
+}
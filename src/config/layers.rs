@@ -0,0 +1,179 @@
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde_yaml::{Mapping, Value};
+
+/// The prefix of the environment variables that override the configuration
+const ENV_PREFIX: &str = "READCHAT2_";
+
+/// Merges `layer` into `base`
+///
+/// Mappings are merged key by key, everything else (including lists) is replaced
+pub fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Mapping(base), Value::Mapping(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Reads a layer from a yaml file, if it exists
+pub fn from_file(path: &Path) -> anyhow::Result<Option<Value>> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| anyhow::anyhow!("cannot read {}", path.display()))
+        }
+    };
+
+    // an empty file has nothing to override
+    if data.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Some(Value::Mapping(Mapping::new())));
+    }
+
+    serde_yaml::from_slice(&data)
+        .map(Some)
+        .with_context(|| anyhow::anyhow!("cannot parse {}", path.display()))
+}
+
+/// Builds a layer for each `READCHAT2_*` variable, along with the variable's name
+///
+/// `__` separates nested keys, so `READCHAT2_OVERLAY__PORT=8080` sets `overlay.port`.
+/// Where `schema` has a string, the value is kept as it's written. Everything else is
+/// parsed as yaml, so `true`, `8080` and `[a, b]` keep their types.
+/// Variables that aren't a key in `schema` are ignored
+pub fn from_env(
+    vars: impl IntoIterator<Item = (String, String)>,
+    schema: &Value,
+) -> anyhow::Result<Vec<(String, Value)>> {
+    let mut layers = vec![];
+
+    for (key, value) in vars {
+        let path = match key.strip_prefix(ENV_PREFIX) {
+            Some(path) if !path.is_empty() => path.to_lowercase(),
+            _ => continue,
+        };
+
+        let keys = path.split("__").collect::<Vec<_>>();
+        anyhow::ensure!(
+            keys.iter().all(|key| !key.is_empty()),
+            "{} has an empty key",
+            key
+        );
+
+        let expected = match lookup(schema, &keys) {
+            Some(expected) => expected,
+            None => continue,
+        };
+
+        let value = match expected {
+            // so `READCHAT2_CHANNEL=1234` is still a channel name
+            Value::String(..) => Value::String(value),
+            // the optional keys are strings, unless they are unset with `~`
+            Value::Null if value != "~" => Value::String(value),
+            _ => serde_yaml::from_str(&value)
+                .with_context(|| anyhow::anyhow!("cannot parse the value of {}", key))?,
+        };
+
+        let mut layer = Value::Mapping(Mapping::new());
+        set(&mut layer, &keys, value);
+        layers.push((key, layer));
+    }
+
+    Ok(layers)
+}
+
+/// Finds the value at the path of keys, a mapping without keys in `schema` takes any key
+fn lookup<'a>(schema: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().try_fold(schema, |node, &key| match node {
+        Value::Mapping(mapping) if mapping.is_empty() => Some(node),
+        Value::Mapping(mapping) => mapping.get(&Value::from(key)),
+        _ => None,
+    })
+}
+
+/// Sets the value at the path of keys, creating mappings along the way
+pub fn set(root: &mut Value, keys: &[&str], value: Value) {
+    let (last, parents) = match keys.split_last() {
+        Some(split) => split,
+        None => return *root = value,
+    };
+
+    let mut node = root;
+    for &key in parents {
+        let mapping = as_mapping(node);
+        let key = Value::from(key);
+        if !mapping.contains_key(&key) {
+            mapping.insert(key.clone(), Value::Mapping(Mapping::new()));
+        }
+        node = mapping.get_mut(&key).expect("key was just inserted");
+    }
+    as_mapping(node).insert((*last).into(), value);
+}
+
+fn as_mapping(value: &mut Value) -> &mut Mapping {
+    if !value.is_mapping() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    match value {
+        Value::Mapping(mapping) => mapping,
+        _ => unreachable!("value was just made a mapping"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> anyhow::Result<Vec<(String, Value)>> {
+        let schema = serde_yaml::to_value(crate::Config::default())?;
+        let vars = vars.iter().map(|&(k, v)| (k.to_string(), v.to_string()));
+        from_env(vars, &schema)
+    }
+
+    #[test]
+    fn keeps_strings_as_written() {
+        let layers = env(&[("READCHAT2_CHANNEL", "1234")]).unwrap();
+        assert_eq!(layers[0].1["channel"], Value::from("1234"));
+    }
+
+    #[test]
+    fn parses_other_types() {
+        let layers = env(&[
+            ("READCHAT2_OVERLAY__PORT", "8080"),
+            ("READCHAT2_OVERLAY__ENABLED", "true"),
+        ])
+        .unwrap();
+        assert_eq!(layers[0].1["overlay"]["port"], Value::from(8080));
+        assert_eq!(layers[1].1["overlay"]["enabled"], Value::from(true));
+    }
+
+    #[test]
+    fn ignores_unknown_keys() {
+        let layers = env(&[
+            ("READCHAT2_NOPE", "1"),
+            ("READCHAT2_OVERLAY__NOPE", "1"),
+            ("OTHER_CHANNEL", "1"),
+        ])
+        .unwrap();
+        assert!(layers.is_empty());
+    }
+
+    #[test]
+    fn names_the_variable() {
+        let err = env(&[("READCHAT2_OVERLAY__PORT", "[1")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot parse the value of READCHAT2_OVERLAY__PORT"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Notifications {
+    pub highlights: Option<Notify>,
+    pub rate_limit: RateLimit,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Notify {
+    Bell,
+    Osc9,
+    Osc777,
+    Command(NotifyCommand),
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct NotifyCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub json: bool,
+}
+
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RateLimit {
+    pub count: usize,
+    pub per_secs: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            per_secs: 30,
+        }
+    }
+}
+
+impl RateLimit {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.per_secs)
+    }
+}
@@ -0,0 +1,13 @@
+/// How entries are printed when running without the terminal ui
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Output {
+    pub template: String,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            template: "{ts} {badge}{name}: {message}".into(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+/// A local web page showing the chat, for use as a stream overlay
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Overlay {
+    pub enabled: bool,
+    #[serde(default = "Overlay::default_port")]
+    pub port: u16,
+    /// The origin allowed to read the event stream from another page, e.g. `http://localhost:8080`
+    #[serde(default)]
+    pub allowed_origin: Option<String>,
+    #[serde(
+        default = "Overlay::default_max_clients",
+        deserialize_with = "at_least_one"
+    )]
+    pub max_clients: usize,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: Self::default_port(),
+            allowed_origin: None,
+            max_clients: Self::default_max_clients(),
+        }
+    }
+}
+
+impl Overlay {
+    const fn default_port() -> u16 {
+        7331
+    }
+
+    const fn default_max_clients() -> usize {
+        8
+    }
+}
+
+fn at_least_one<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Deserialize as _, Error as _};
+    match usize::deserialize(deserializer)? {
+        0 => Err(D::Error::custom("must be at least 1")),
+        n => Ok(n),
+    }
+}
@@ -0,0 +1,43 @@
+use crate::entry::Badge;
+
+use super::{Notify, Style};
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Rule {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub min_badge: Option<Badge>,
+    #[serde(default)]
+    pub first_message: bool,
+    #[serde(default)]
+    pub bits: bool,
+    #[serde(default)]
+    pub links: bool,
+    #[serde(default)]
+    pub target: Target,
+    pub style: Style,
+    #[serde(default)]
+    pub highlight: bool,
+    #[serde(default)]
+    pub notify: Option<Notify>,
+}
+
+impl Rule {
+    pub fn has_conditions(&self) -> bool {
+        !self.users.is_empty()
+            || self.min_badge.is_some()
+            || self.first_message
+            || self.bits
+            || self.links
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    Name,
+    #[default]
+    Message,
+    Row,
+}
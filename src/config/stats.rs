@@ -0,0 +1,5 @@
+#[derive(Copy, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Stats {
+    /// Writes the statistics for the session to the data directory on exit
+    pub export: bool,
+}
@@ -22,6 +22,7 @@ impl Style {
     }
 }
 
+#[cfg(feature = "tui")]
 impl From<Style> for cursive::theme::Style {
     fn from(style: Style) -> Self {
         use cursive::theme::{Color as CursiveColor, ColorStyle};
@@ -1,11 +1,16 @@
+#[cfg(feature = "tui")]
 use crate::ui::Tab;
 
-use super::{Color, Style};
+use super::{Color, Effects, Style};
 
 #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Tabs {
     pub active: Style,
     pub inactive: Style,
+    #[serde(default = "Tabs::default_unread")]
+    pub unread: Style,
+    #[serde(default = "Tabs::default_alert")]
+    pub alert: Style,
 }
 
 impl Default for Tabs {
@@ -13,6 +18,21 @@ impl Default for Tabs {
         Self {
             active: Style::fg(Color::RED),
             inactive: Style::fg(Color::WHITE),
+            unread: Self::default_unread(),
+            alert: Self::default_alert(),
+        }
+    }
+}
+
+impl Tabs {
+    fn default_unread() -> Style {
+        Style::fg(Color::WHITE).with_effects(Effects::bold())
+    }
+
+    fn default_alert() -> Style {
+        Style {
+            bg: Some(Color::RED),
+            ..Style::fg(Color::WHITE).with_effects(Effects::bold())
         }
     }
 }
@@ -23,6 +43,8 @@ pub struct TabNameMapping {
     pub messages: String,
     pub links: String,
     pub highlights: String,
+    #[serde(default = "TabNameMapping::default_stats")]
+    pub stats: String,
 }
 
 impl Default for TabNameMapping {
@@ -32,11 +54,17 @@ impl Default for TabNameMapping {
             messages: "Messages".to_string(),
             links: "Links".to_string(),
             highlights: "Highlights".to_string(),
+            stats: Self::default_stats(),
         }
     }
 }
 
 impl TabNameMapping {
+    fn default_stats() -> String {
+        "Stats".to_string()
+    }
+
+    #[cfg(feature = "tui")]
     pub fn as_tabs(&self) -> impl Iterator<Item = Tab<'_>> + '_ {
         self.iter()
             .enumerate()
@@ -49,6 +77,7 @@ impl TabNameMapping {
             &*self.messages,
             &*self.links,
             &*self.highlights,
+            &*self.stats,
         ])
     }
 }
@@ -1,8 +1,14 @@
-use std::io::Write;
+use std::{io::Write, net::Shutdown, thread::JoinHandle};
 
 use twitchchat::{commands::raw, Encoder};
 
-use crate::twitch::Update;
+use crate::{
+    config,
+    hook::Hooked,
+    keepalive::Keepalive,
+    twitch::{Request, Update, UpdateSink},
+    Context,
+};
 
 pub enum ChatMode {
     Real(String),
@@ -10,12 +16,51 @@ pub enum ChatMode {
 }
 
 impl ChatMode {
-    pub fn connect(
+    /// Returns a function that connects to the chat in the background and starts delivering updates to a sink
+    ///
+    /// The returned handle finishes when the connection is closed, or when the sink stops listening.
+    /// Why the connection ended is delivered to the sink as [`Update::Disconnected`].
+    /// Requests made through the `context` are sent on this connection
+    pub fn connect<S>(
         self,
+        context: &Context,
         logger: impl Write + Send + Sync + 'static,
-    ) -> anyhow::Result<impl FnOnce(cursive::CbSink)> {
-        let (updates_tx, updates_rx) = flume::unbounded();
-        updates_tx.send(Update::Connecting)?;
+    ) -> impl FnOnce(S) -> JoinHandle<anyhow::Result<()>>
+    where
+        S: UpdateSink,
+    {
+        let context = context.clone();
+        move |sink: S| {
+            std::thread::spawn(move || {
+                let (hook, keepalive) = {
+                    let config = context.config();
+                    (config.hook.clone(), config.keepalive)
+                };
+                let mut sink = Hooked::new(sink, hook);
+
+                let (requests_tx, requests_rx) = flume::unbounded();
+                context.set_requests(requests_tx);
+
+                let result = self.run(&mut sink, keepalive, requests_rx, logger);
+                if let Err(err) = &result {
+                    sink.deliver(Update::Disconnected(format!("{:#}", err)));
+                }
+                result
+            })
+        }
+    }
+
+    /// Connects and delivers updates to the sink until the connection ends, or the sink stops listening
+    pub(crate) fn run(
+        self,
+        sink: &mut impl UpdateSink,
+        keepalive: config::Keepalive,
+        requests_rx: flume::Receiver<Request>,
+        logger: impl Write + Send + Sync + 'static,
+    ) -> anyhow::Result<()> {
+        if !sink.deliver(Update::Connecting) {
+            return Ok(());
+        }
 
         let (channel, stream) = match self {
             Self::Real(channel) => {
@@ -25,33 +70,42 @@ impl ChatMode {
             Self::Simulated => crate::simulated::simulated_twitch_chat()?,
         };
 
-        let cb = move |sink: cursive::CbSink| {
-            let (activity_tx, activity_rx) = flume::unbounded();
-
-            let read_handle = std::thread::spawn({
-                let channel = channel.to_string();
-                let stream = stream.clone();
-                move || crate::twitch::read_loop(stream, &channel, updates_tx, activity_tx)
-            });
-
-            std::thread::spawn::<_, anyhow::Result<()>>({
-                let stream = stream.clone();
-                move || {
-                    let stream = stream;
-                    crate::twitch::inner_loop(
-                        Encoder::new(&*stream),
-                        updates_rx,
-                        activity_rx,
-                        sink,
-                        logger,
-                    )?;
-                    let _ = Encoder::new(&*stream).encode(raw("QUIT :leaving"));
-                    let _ = read_handle.join();
-                    Ok(())
-                }
-            });
+        let (updates_tx, updates_rx) = flume::unbounded();
+        let (activity_tx, activity_rx) = flume::unbounded();
+
+        let read_handle = std::thread::spawn({
+            let stream = stream.clone();
+            move || crate::twitch::read_loop(stream, &channel, updates_tx, activity_tx)
+        });
+
+        let listening = match crate::twitch::inner_loop(
+            Encoder::new(&*stream),
+            updates_rx,
+            activity_rx,
+            requests_rx,
+            Keepalive::new(keepalive),
+            sink,
+            logger,
+        ) {
+            Ok(listening) => listening,
+            Err(err) => {
+                // the reader is still blocked on the socket, so wake it up
+                let _ = stream.shutdown(Shutdown::Both);
+                let _ = read_handle.join();
+                return Err(err);
+            }
         };
 
-        Ok(cb)
+        if !listening {
+            let _ = Encoder::new(&*stream).encode(raw("QUIT :leaving"));
+            let _ = read_handle.join();
+            return Ok(());
+        }
+
+        match read_handle.join() {
+            Ok(Ok(())) => anyhow::bail!("the server closed the connection"),
+            Ok(Err(err)) => Err(err),
+            Err(..) => anyhow::bail!("the connection's reader panicked"),
+        }
     }
 }
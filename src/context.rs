@@ -0,0 +1,158 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "tui")]
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Mutex, MutexGuard},
+    time::Instant,
+};
+
+#[cfg(feature = "tui")]
+use crate::{poll::Poll, stats::Stats, ui::Unread};
+use crate::{twitch::Request, Config};
+
+/// The configuration, and what is known about the connection, for one chat
+///
+/// Clones share the same state, so one is handed to everything that shows the chat.
+/// The terminal ui keeps it in the cursive user data
+#[derive(Clone, Default)]
+pub struct Context {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    config: RwLock<Config>,
+    session: RwLock<Session>,
+    #[cfg(feature = "tui")]
+    shown: Shown,
+}
+
+/// What is shown of the chat, and the things that follow from it
+#[cfg(feature = "tui")]
+#[derive(Default)]
+struct Shown {
+    stats: Mutex<Stats>,
+    poll: Mutex<Option<Poll>>,
+    // tab index -> what arrived while it wasn't active
+    unread: Mutex<BTreeMap<usize, Unread>>,
+    // the overlay's event streams
+    subscribers: Mutex<Vec<flume::Sender<String>>>,
+    // when the recent notifications were sent, for the rate limit
+    notified: Mutex<VecDeque<Instant>>,
+    // the users ignored with the keybind, kept when the configuration is reloaded
+    ignored: Mutex<Vec<String>>,
+}
+
+#[derive(Default)]
+struct Session {
+    our_name: Option<Arc<str>>,
+    channel: Option<Arc<str>>,
+    requests: Option<flume::Sender<Request>>,
+}
+
+impl Context {
+    pub fn new(config: Config) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config: RwLock::new(config),
+                session: RwLock::default(),
+                #[cfg(feature = "tui")]
+                shown: Shown::default(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> RwLockReadGuard<'_, Config> {
+        self.inner.config.read().unwrap()
+    }
+
+    pub fn config_mut(&self) -> RwLockWriteGuard<'_, Config> {
+        self.inner.config.write().unwrap()
+    }
+
+    /// Reads the configuration without waiting, for when the lock may be held by a panicking thread
+    pub(crate) fn try_config(&self) -> Option<RwLockReadGuard<'_, Config>> {
+        match self.inner.config.try_read() {
+            Ok(config) => Some(config),
+            Err(std::sync::TryLockError::Poisoned(config)) => Some(config.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// The name we logged in with, unless it was an anonymous login
+    pub fn our_name(&self) -> Option<Arc<str>> {
+        self.inner.session.read().unwrap().our_name.clone()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn set_our_name(&self, name: String) {
+        // anonymous logins are always some variant of 'justinfan1234'
+        if !name.starts_with("justinfan") {
+            self.inner.session.write().unwrap().our_name = Some(name.into());
+        }
+    }
+
+    /// The channel we're in, if any
+    pub fn channel(&self) -> Option<Arc<str>> {
+        self.inner.session.read().unwrap().channel.clone()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn set_channel(&self, channel: Option<&str>) {
+        self.inner.session.write().unwrap().channel = channel.map(Into::into);
+    }
+
+    pub(crate) fn set_requests(&self, requests: flume::Sender<Request>) {
+        self.inner
+            .session
+            .write()
+            .unwrap()
+            .requests
+            .replace(requests);
+    }
+
+    /// Sends a request to the connection
+    #[cfg(feature = "tui")]
+    pub(crate) fn send_request(&self, request: Request) -> anyhow::Result<()> {
+        self.inner
+            .session
+            .read()
+            .unwrap()
+            .requests
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("not connected"))?
+            .send(request)
+            .map_err(|_| anyhow::anyhow!("the connection has closed"))
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn stats(&self) -> MutexGuard<'_, Stats> {
+        self.inner.shown.stats.lock().unwrap()
+    }
+
+    /// The running poll, if there is one
+    #[cfg(feature = "tui")]
+    pub(crate) fn poll(&self) -> MutexGuard<'_, Option<Poll>> {
+        self.inner.shown.poll.lock().unwrap()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn unread(&self) -> MutexGuard<'_, BTreeMap<usize, Unread>> {
+        self.inner.shown.unread.lock().unwrap()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn subscribers(&self) -> MutexGuard<'_, Vec<flume::Sender<String>>> {
+        self.inner.shown.subscribers.lock().unwrap()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn notified(&self) -> MutexGuard<'_, VecDeque<Instant>> {
+        self.inner.shown.notified.lock().unwrap()
+    }
+
+    #[cfg(feature = "tui")]
+    pub(crate) fn ignored(&self) -> MutexGuard<'_, Vec<String>> {
+        self.inner.shown.ignored.lock().unwrap()
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+use cursive::{CbSink, Cursive};
+
+use crate::{
+    ui::{MainScreen, OnView as _},
+    App,
+};
+
+/// A command read from the control socket
+#[derive(Debug)]
+enum Command {
+    Focus(usize),
+    Keyword(String),
+    Note(String),
+    Highlights,
+    StartPoll(String),
+    EndPoll,
+    Join(String),
+    Part,
+    Switch(String),
+}
+
+impl std::str::FromStr for Command {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> anyhow::Result<Self> {
+        let (head, tail) = line
+            .trim()
+            .split_once(' ')
+            .map(|(head, tail)| (head, tail.trim()))
+            .unwrap_or((line.trim(), ""));
+
+        let cmd = match (head, tail) {
+            ("focus", tab) => Self::Focus(match tab {
+                "status" | "0" => MainScreen::STATUS_VIEW_INDEX,
+                "messages" | "1" => MainScreen::MESSAGE_VIEW_INDEX,
+                "links" | "2" => MainScreen::LINKS_VIEW_INDEX,
+                "highlights" | "3" => MainScreen::HIGHLIGHTS_VIEW_INDEX,
+                "stats" | "4" => MainScreen::STATS_VIEW_INDEX,
+                tab => anyhow::bail!("unknown tab: '{}'", tab),
+            }),
+            ("keyword" | "note" | "join" | "switch", "") => {
+                anyhow::bail!("{} needs an argument", head)
+            }
+            ("keyword", word) => Self::Keyword(word.to_string()),
+            ("note", note) => Self::Note(note.to_string()),
+            ("highlights", "") => Self::Highlights,
+            ("poll", "end") => Self::EndPoll,
+            ("poll", options) => Self::StartPoll(options.to_string()),
+            ("join", channel) => Self::Join(channel.to_string()),
+            ("part", "") => Self::Part,
+            ("switch", channel) => Self::Switch(channel.to_string()),
+            (head, ..) => anyhow::bail!("unknown command: '{}'", head),
+        };
+        Ok(cmd)
+    }
+}
+
+impl Command {
+    fn run(self, cursive: &mut Cursive) -> String {
+        match self {
+            Self::Focus(index) => MainScreen::with(cursive).focus(index),
+            Self::Keyword(word) => App::add_keyword(cursive, word),
+            Self::Note(note) => App::append_note(cursive, note),
+            Self::Highlights => return App::dump_highlights(cursive).to_string(),
+            Self::StartPoll(options) => return reply(App::start_poll(cursive, &options)),
+            Self::EndPoll => App::end_poll(cursive),
+            Self::Join(channel) => return reply(App::join_channel(cursive, &channel)),
+            Self::Part => return reply(App::part_channel(cursive)),
+            Self::Switch(channel) => return reply(App::switch_channel(cursive, &channel)),
+        }
+        "ok".to_string()
+    }
+}
+
+fn reply(result: anyhow::Result<()>) -> String {
+    match result {
+        Ok(()) => "ok".to_string(),
+        Err(err) => format!("error: {}", err),
+    }
+}
+
+/// Removes the socket file when the program exits
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Listens on the socket at `path`, running each command on the ui thread
+pub fn listen(path: &Path, sink: CbSink) -> anyhow::Result<ControlSocket> {
+    if path.exists() {
+        // a socket that nobody is listening on was left behind by a crash
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!("{} is being used by another instance", path.display())
+        }
+        std::fs::remove_file(path)?;
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sink = sink.clone();
+            std::thread::spawn(move || handle(stream, sink));
+        }
+    });
+
+    Ok(ControlSocket {
+        path: path.to_path_buf(),
+    })
+}
+
+fn handle(stream: UnixStream, sink: CbSink) -> anyhow::Result<()> {
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match line.parse::<Command>() {
+            Ok(cmd) => {
+                let (tx, rx) = flume::bounded(1);
+                sink.send(Box::new(move |cursive| {
+                    let _ = tx.send(cmd.run(cursive));
+                }))
+                .map_err(|_| anyhow::anyhow!("the ui has closed"))?;
+                rx.recv()?
+            }
+            Err(err) => format!("error: {}", err),
+        };
+
+        writeln!(writer, "{}", reply)?;
+    }
+    Ok(())
+}
@@ -1,149 +1,44 @@
-use cursive::{
-    direction::Orientation,
-    theme::Color,
-    traits::{Boxable, Nameable},
-    utils::span::SpannedString,
-    view::Margins,
-    views::*,
-    View,
-};
+use std::collections::HashMap;
 
 use twitchchat::messages::Privmsg;
 
 use crate::{
-    config::{Highlights, Keyword, Style},
-    get_config,
-    ui::SpannedAppender,
+    config::{Color, Highlights, Ignore, Keyword, Notify, Rule, Style},
     Config,
 };
 
 mod badge;
 pub use badge::Badge;
 
+#[cfg(feature = "tui")]
+mod views;
+
 #[derive(Clone, Debug)]
 pub struct Entry {
     pub name: String,
+    pub login: String,
     pub data: String,
     pub badge: Option<Badge>,
     pub ts: chrono::DateTime<chrono::Local>,
     pub color: Color,
+    pub reply_to: Option<String>,
+    pub first_message: bool,
+    pub bits: Option<u64>,
+    pub emotes: Vec<String>,
+    pub tags: Vec<String>,
+    pub highlight: Option<Style>,
+    pub notify: Option<Notify>,
 }
 
 impl Entry {
-    pub(crate) fn as_header_view(entry: &Self) -> impl View {
-        let ts = {
-            let Config {
-                timestamp_fmt,
-                colors,
-                ..
-            } = &*get_config();
-
-            let ts = entry.ts.format(timestamp_fmt).to_string();
-            SpannedString::styled(ts, colors.timestamp)
-        };
-
-        let left = {
-            let name = SpannedString::styled(&*entry.name.trim(), entry.color);
-            let tv = TextView::new(name).no_wrap().full_width();
-            let mut sub = LinearLayout::new(Orientation::Horizontal).child(tv);
-            if let Some(badge) = entry.badge {
-                let tv = PaddedView::new(
-                    Margins::lr(0, 1),
-                    TextView::new(badge.as_spanned_string()).no_wrap(),
-                );
-                sub.add_child(HideableView::new(tv).with_name("badge"))
-            }
-            sub
-        };
-
-        let tv = TextView::new(ts).no_wrap();
-        let right = HideableView::new(tv).with_name("timestamp");
-
-        LinearLayout::new(Orientation::Horizontal)
-            .child(left)
-            .child(right)
-    }
-
-    pub(crate) fn as_message_view(&self) -> Option<impl View> {
-        let Highlights { mention, keywords } = &get_config().highlights;
-        self.as_row_entry(&**keywords, *mention)
-    }
-
-    pub(crate) fn as_links_view(&self) -> Option<impl View> {
-        if !self.contains_links() {
-            return None;
-        }
-
-        Some(
-            self.find_links()
-                .fold(
-                    LinearLayout::new(Orientation::Vertical).child(Self::as_header_view(self)),
-                    |layout, link| layout.child(TextView::new(&*link).full_width()),
-                )
-                .child(TextView::new("\n"))
-                .full_width(),
-        )
-    }
-
-    pub(crate) fn as_highlights_view(&self) -> Option<impl View> {
-        let Highlights { mention, keywords } = &get_config().highlights;
-        let name = &*crate::state::get_channel();
-
-        if !self.contains_keywords(keywords) && !self.contains_mention(name) {
-            return None;
-        }
-
-        self.as_row_entry(&**keywords, *mention)
-    }
-
-    fn as_row_entry(&self, keywords: &[Keyword], style: Style) -> Option<impl View> {
-        let name = &*crate::state::get_channel();
-        Some(
-            LinearLayout::new(Orientation::Vertical)
-                .child(Self::as_header_view(self))
-                .child(TextView::new(self.highlight(keywords, name, style)))
-                .child(TextView::new("\n")),
-        )
-    }
-}
-
-impl Entry {
-    pub(crate) fn highlight(
-        &self,
-        keywords: &[Keyword],
-        name: &str,
-        style: Style,
-    ) -> SpannedString<cursive::theme::Style> {
-        let mut string = self.find_keywords(keywords).fold(
-            SpannedString::<cursive::theme::Style>::new(),
-            |mut s, part| {
-                if !s.is_empty() {
-                    s = s.append_plain(" ");
-                }
-                match part {
-                    Part::Matched(text, style) => s.append(text, style),
-                    Part::NotMatched(text) => s.append_plain(text),
-                }
-            },
-        );
-
-        for span in string.spans_attr_mut() {
-            if trim_punc(span.content).eq_ignore_ascii_case(name) {
-                *span.attr = span.attr.combine(style);
-            }
-        }
-
-        string
-    }
-
-    pub(crate) fn contains_links(&self) -> bool {
+    pub fn contains_links(&self) -> bool {
         self.data
             .split_whitespace()
             .flat_map(url::Url::parse)
             .any(|url| matches!(url.scheme(), "http" | "https"))
     }
 
-    pub(crate) fn find_links(&self) -> impl Iterator<Item = String> + '_ {
+    pub fn find_links(&self) -> impl Iterator<Item = String> + '_ {
         self.data
             .split_whitespace()
             .flat_map(url::Url::parse)
@@ -151,7 +46,7 @@ impl Entry {
             .map(Into::into)
     }
 
-    pub(crate) fn find_keywords<'a: 'b, 'b>(
+    pub fn find_keywords<'a: 'b, 'b>(
         &'b self,
         keywords: &'a [Keyword],
     ) -> impl Iterator<Item = Part<'b>> + 'b {
@@ -159,23 +54,176 @@ impl Entry {
             let trimmed = trim_punc(s);
             keywords
                 .iter()
-                .find_map(|kw| (kw == trimmed).then(|| (trimmed, kw.style)))
+                .find_map(|kw| (kw == trimmed).then_some((trimmed, kw.style)))
                 .map(|(n, s)| Part::Matched(n, s))
                 .unwrap_or_else(|| Part::NotMatched(s))
         })
     }
 
-    pub(crate) fn contains_mention(&self, name: &str) -> bool {
+    pub(crate) fn contains_mention(&self, names: &[&str]) -> bool {
         self.data
             .split_whitespace()
-            .any(|s| trim_punc(s).eq_ignore_ascii_case(name))
+            .map(trim_punc)
+            .any(|s| names.iter().any(|name| s.eq_ignore_ascii_case(name)))
     }
 
-    pub(crate) fn contains_keywords(&self, keywords: &[Keyword]) -> bool {
+    pub(crate) fn is_reply_to(&self, names: &[&str]) -> bool {
+        self.reply_to
+            .as_deref()
+            .is_some_and(|reply| names.iter().any(|name| reply.eq_ignore_ascii_case(name)))
+    }
+
+    pub(crate) fn is_mention(&self, highlights: &Highlights, names: &[&str]) -> bool {
+        self.contains_mention(names) || (highlights.replies && self.is_reply_to(names))
+    }
+
+    pub fn contains_keywords(&self, keywords: &[Keyword]) -> bool {
         self.data
             .split_whitespace()
             .any(|s| keywords.iter().any(|kw| kw == s))
     }
+
+    /// How alike the text of two entries is, from `0.0` to `1.0`
+    ///
+    /// This ignores case and whitespace, and compares the pairs of adjacent characters
+    pub fn similarity(&self, other: &Self) -> f32 {
+        fn normalize(data: &str) -> Vec<char> {
+            data.split_whitespace()
+                .flat_map(|s| s.chars().chain(std::iter::once(' ')))
+                .flat_map(char::to_lowercase)
+                .collect()
+        }
+
+        fn bigrams(data: &[char]) -> HashMap<(char, char), usize> {
+            data.windows(2).fold(HashMap::new(), |mut map, pair| {
+                *map.entry((pair[0], pair[1])).or_default() += 1;
+                map
+            })
+        }
+
+        let (left, right) = (normalize(&self.data), normalize(&other.data));
+        if left == right {
+            return 1.0;
+        }
+
+        if left.len() < 2 || right.len() < 2 {
+            return 0.0;
+        }
+
+        let (left, right) = (bigrams(&left), bigrams(&right));
+        let shared = left
+            .iter()
+            .map(|(k, &n)| right.get(k).map_or(0, |&m| n.min(m)))
+            .sum::<usize>();
+
+        let total = left.values().chain(right.values()).sum::<usize>();
+        (2 * shared) as f32 / total as f32
+    }
+
+    /// The notification for this entry, from the hook, the first matching rule that has one
+    /// or from the highlights when it is a mention or contains a keyword
+    pub fn notification<'a>(
+        &'a self,
+        config: &'a Config,
+        our_name: Option<&str>,
+    ) -> Option<&'a Notify> {
+        if let Some(notify) = &self.notify {
+            return Some(notify);
+        }
+
+        let highlights = &config.highlights;
+        if let Some(notify) = self
+            .matching_rules(&highlights.rules)
+            .find_map(|rule| rule.notify.as_ref())
+        {
+            return Some(notify);
+        }
+
+        let names = highlights.mention_names(our_name);
+        if self.contains_keywords(&highlights.keywords) || self.is_mention(highlights, &names) {
+            return config.notifications.highlights.as_ref();
+        }
+
+        None
+    }
+
+    /// Whether this would be shown in the highlights view
+    pub fn is_highlighted(&self, highlights: &Highlights, our_name: Option<&str>) -> bool {
+        self.highlight.is_some()
+            || self.contains_keywords(&highlights.keywords)
+            || self.mentions_us(highlights, our_name)
+            || self
+                .matching_rules(&highlights.rules)
+                .any(|rule| rule.highlight)
+    }
+
+    pub fn as_json(&self, channel: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name.trim(),
+            "login": self.login,
+            "channel": channel,
+            "message": self.data,
+            "ts": self.ts.to_rfc3339(),
+            "badge": self.badge,
+            "reply_to": self.reply_to,
+            "first_message": self.first_message,
+            "bits": self.bits,
+            "tags": self.tags,
+        })
+    }
+
+    /// Whether this mentions, or replies to, one of our configured names or `our_name`
+    pub fn mentions_us(&self, highlights: &Highlights, our_name: Option<&str>) -> bool {
+        let names = highlights.mention_names(our_name);
+        self.is_mention(highlights, &names)
+    }
+
+    pub fn is_ignored(&self, ignore: &Ignore) -> bool {
+        ignore.matches(&self.login, &self.data)
+    }
+
+    pub fn matching_rules<'a: 'b, 'b>(
+        &'b self,
+        rules: &'a [Rule],
+    ) -> impl Iterator<Item = &'a Rule> + 'b {
+        rules
+            .iter()
+            .filter(move |rule| rule.has_conditions() && self.matches_rule(rule))
+    }
+
+    fn matches_rule(&self, rule: &Rule) -> bool {
+        (rule.users.is_empty()
+            || rule
+                .users
+                .iter()
+                .any(|user| user.eq_ignore_ascii_case(&self.login)))
+            && rule.min_badge.is_none_or(|min| self.badge >= Some(min))
+            && (!rule.first_message || self.first_message)
+            && (!rule.bits || self.bits.is_some())
+            && (!rule.links || self.contains_links())
+    }
+}
+
+#[cfg(test)]
+impl Entry {
+    /// A plain message, as if `name` had sent it
+    pub(crate) fn message(name: &str, data: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            login: name.to_lowercase(),
+            data: data.to_string(),
+            badge: None,
+            ts: chrono::Local::now(),
+            color: Color::default(),
+            reply_to: None,
+            first_message: false,
+            bits: None,
+            emotes: Vec::new(),
+            tags: Vec::new(),
+            highlight: None,
+            notify: None,
+        }
+    }
 }
 
 fn trim_punc(mut input: &str) -> &str {
@@ -193,11 +241,12 @@ impl<'a> From<Privmsg<'a>> for Entry {
         use twitchchat::twitch::{color::RGB, Color as TwitchColor};
         let conv = |color: TwitchColor| {
             let RGB(r, g, b) = color.rgb;
-            Color::Rgb(r, g, b)
+            Color(r, g, b)
         };
 
         Self {
             name: pm.display_name().unwrap_or_else(|| pm.name()).to_string(),
+            login: pm.name().to_string(),
             data: pm.data().to_string(),
             ts: chrono::Local::now(),
             badge: pm
@@ -205,6 +254,27 @@ impl<'a> From<Privmsg<'a>> for Entry {
                 .flat_map(|b| Badge::from_badge_kind(&b.kind))
                 .max(),
             color: conv(pm.color().unwrap_or_default()),
+            reply_to: pm
+                .tags()
+                .get("reply-parent-user-login")
+                .map(ToString::to_string),
+            first_message: pm.tags().get_as_bool("first-msg"),
+            bits: pm.bits(),
+            emotes: pm
+                .iter_emotes()
+                .flat_map(|emote| emote.ranges)
+                // the ranges are inclusive, and count characters
+                .map(|range| {
+                    pm.data()
+                        .chars()
+                        .skip(range.start as _)
+                        .take((range.end - range.start + 1) as _)
+                        .collect()
+                })
+                .collect(),
+            tags: Vec::new(),
+            highlight: None,
+            notify: None,
         }
     }
 }
@@ -213,3 +283,42 @@ pub enum Part<'a> {
     Matched(&'a str, Style),
     NotMatched(&'a str),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn similarity(left: &str, right: &str) -> f32 {
+        Entry::message("museun", left).similarity(&Entry::message("museun", right))
+    }
+
+    #[test]
+    fn similarity_of_short_messages() {
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("", "hello"), 0.0);
+        assert_eq!(similarity("a", "a"), 1.0);
+        assert_eq!(similarity("a", "b"), 0.0);
+        assert_eq!(similarity("a", "ab"), 0.0);
+    }
+
+    #[test]
+    fn similarity_ignores_case_and_whitespace() {
+        assert_eq!(similarity("hello world", "hello world"), 1.0);
+        assert_eq!(similarity("Hello   World ", " hello world"), 1.0);
+        assert_eq!(similarity("hello world", "helloworld"), 18.0 / 21.0);
+    }
+
+    #[test]
+    fn similarity_around_the_threshold() {
+        let threshold = crate::config::Dedup::default().similarity;
+
+        // one letter added to n letters shares n - 1 of the bigrams
+        let under = similarity("abcdefghijklmn", "abcdefghijklmno");
+        assert_eq!(under, 26.0 / 29.0);
+        assert!(under < threshold);
+
+        let over = similarity("abcdefghijklmno", "abcdefghijklmnop");
+        assert_eq!(over, 28.0 / 31.0);
+        assert!(over >= threshold);
+    }
+}
@@ -1,10 +1,15 @@
+#[cfg(feature = "tui")]
 use cursive::{theme::Style, utils::span::SpannedString};
 use twitchchat::twitch::BadgeKind;
 
-use crate::get_config;
+#[cfg(feature = "tui")]
+use crate::Config;
 
 // NOTE: this must remain in this order for Iterator::max to work
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(
+    Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
 pub enum Badge {
     Partner,
     Vip,
@@ -20,9 +25,8 @@ pub enum Badge {
 }
 
 impl Badge {
-    pub(crate) fn as_spanned_string(&self) -> SpannedString<Style> {
-        let config = get_config();
-
+    #[cfg(feature = "tui")]
+    pub(crate) fn as_spanned_string(&self, config: &Config) -> SpannedString<Style> {
         let badges = &config.colors.badges;
         let mapping = &config.badge_names;
 
@@ -0,0 +1,283 @@
+use cursive::{
+    direction::Orientation, traits::Boxable, utils::span::SpannedString, view::Margins, views::*,
+    View,
+};
+
+use super::{trim_punc, Entry, Part};
+use crate::{
+    config::{Highlights, Keyword, Rule, Style, Target},
+    ui::{SpannedAppender, Toggled},
+    Config, Context,
+};
+
+impl Entry {
+    pub(crate) fn as_header_view(entry: &Self, context: &Context) -> impl View {
+        let config = context.config();
+        let left = {
+            let tv = TextView::new(entry.styled_name(&config))
+                .no_wrap()
+                .full_width();
+            let mut sub = LinearLayout::new(Orientation::Horizontal).child(tv);
+            if let Some(badge) = entry.badge {
+                let tv = PaddedView::new(
+                    Margins::lr(0, 1),
+                    TextView::new(badge.as_spanned_string(&config)).no_wrap(),
+                );
+                sub.add_child(Toggled::new(tv, context, |config| config.badges))
+            }
+            sub
+        };
+
+        let tv = TextView::new(entry.styled_timestamp(&config)).no_wrap();
+        let right = Toggled::new(tv, context, |config| config.timestamps);
+
+        LinearLayout::new(Orientation::Horizontal)
+            .child(left)
+            .child(right)
+    }
+
+    pub(crate) fn as_message_view(&self, context: &Context) -> Option<impl View> {
+        Some(self.as_row_entry(context))
+    }
+
+    pub(crate) fn as_links_view(&self, context: &Context) -> Option<impl View> {
+        if !self.contains_links() {
+            return None;
+        }
+
+        let header = Self::as_header_view(self, context);
+        Some(
+            self.find_links()
+                .fold(
+                    LinearLayout::new(Orientation::Vertical).child(header),
+                    |layout, link| layout.child(TextView::new(&*link).full_width()),
+                )
+                .child(TextView::new("\n"))
+                .full_width(),
+        )
+    }
+
+    pub(crate) fn as_highlights_view(&self, context: &Context) -> Option<impl View> {
+        let our_name = context.our_name();
+        if !self.is_highlighted(&context.config().highlights, our_name.as_deref()) {
+            return None;
+        }
+
+        Some(self.as_row_entry(context))
+    }
+
+    pub(crate) fn as_repeated_view(
+        &self,
+        context: &Context,
+        count: usize,
+        senders: &[String],
+    ) -> impl View {
+        const MAX_SENDERS: usize = 10;
+
+        let skip = senders.len().saturating_sub(MAX_SENDERS);
+        let more = if skip > 0 {
+            format!(" +{}", skip)
+        } else {
+            String::new()
+        };
+
+        let text = SpannedString::default()
+            .append(format!("\u{d7}{}", count), cursive::theme::Effect::Bold)
+            .append_plain(more)
+            .append_plain(" ")
+            .append(senders[skip..].join(", "), cursive::theme::Effect::Italic);
+
+        LinearLayout::new(Orientation::Vertical)
+            .child(Self::as_header_view(self, context))
+            .child(TextView::new(self.highlighted_message(context)))
+            .child(TextView::new(text))
+            .child(TextView::new("\n"))
+    }
+
+    pub(crate) fn as_ignored_view(&self) -> impl View {
+        let text = SpannedString::styled(self.name.trim(), self.color)
+            .append(": message hidden", cursive::theme::Effect::Italic);
+
+        LinearLayout::new(Orientation::Vertical)
+            .child(TextView::new(text).no_wrap())
+            .child(TextView::new("\n"))
+    }
+
+    fn as_row_entry(&self, context: &Context) -> impl View {
+        LinearLayout::new(Orientation::Vertical)
+            .child(Self::as_header_view(self, context))
+            .child(TextView::new(self.highlighted_message(context)))
+            .child(TextView::new("\n"))
+    }
+
+    fn highlighted_message(&self, context: &Context) -> SpannedString<cursive::theme::Style> {
+        let our_name = context.our_name();
+        self.highlight_with(&context.config(), our_name.as_deref())
+    }
+
+    pub(crate) fn styled_timestamp(&self, config: &Config) -> SpannedString<cursive::theme::Style> {
+        let styling = Styling::from_rules(self.matching_rules(&config.highlights.rules));
+        let ts = self.ts.format(&config.timestamp_fmt).to_string();
+        SpannedString::styled(ts, styling.row.unwrap_or(config.colors.timestamp))
+    }
+
+    /// The name, followed by the tags given to this entry by the hook
+    pub(crate) fn styled_name(&self, config: &Config) -> SpannedString<cursive::theme::Style> {
+        let styling = Styling::from_rules(self.matching_rules(&config.highlights.rules));
+        let mut name = SpannedString::styled(self.name.trim(), self.color);
+        styling.apply_name(&mut name);
+
+        self.tags.iter().fold(name, |name, tag| {
+            name.append_plain(" ")
+                .append(format!("[{}]", tag), cursive::theme::Effect::Italic)
+        })
+    }
+
+    pub(crate) fn highlight_with(
+        &self,
+        config: &Config,
+        our_name: Option<&str>,
+    ) -> SpannedString<cursive::theme::Style> {
+        let Highlights {
+            mention,
+            keywords,
+            rules,
+            ..
+        } = &config.highlights;
+
+        let names = config.highlights.mention_names(our_name);
+
+        let mut text = self.highlight(keywords, &names, *mention);
+        let mut styling = Styling::from_rules(self.matching_rules(rules));
+        // the hook has the final say on the style
+        if let Some(style) = self.highlight {
+            styling.message.replace(style);
+        }
+        styling.apply_message(&mut text);
+        text
+    }
+
+    pub(crate) fn highlight(
+        &self,
+        keywords: &[Keyword],
+        names: &[&str],
+        style: Style,
+    ) -> SpannedString<cursive::theme::Style> {
+        let mut string = self.find_keywords(keywords).fold(
+            SpannedString::<cursive::theme::Style>::new(),
+            |mut s, part| {
+                if !s.is_empty() {
+                    s = s.append_plain(" ");
+                }
+                match part {
+                    Part::Matched(text, style) => s.append(text, style),
+                    Part::NotMatched(text) => s.append_plain(text),
+                }
+            },
+        );
+
+        for span in string.spans_attr_mut() {
+            let content = trim_punc(span.content);
+            if names.iter().any(|name| content.eq_ignore_ascii_case(name)) {
+                *span.attr = span.attr.combine(style);
+            }
+        }
+
+        string
+    }
+}
+
+/// The styles picked by the first matching [`Rule`] for each [`Target`]
+#[derive(Default)]
+struct Styling {
+    name: Option<Style>,
+    message: Option<Style>,
+    row: Option<Style>,
+}
+
+impl Styling {
+    fn from_rules<'a>(rules: impl IntoIterator<Item = &'a Rule>) -> Self {
+        rules.into_iter().fold(Self::default(), |mut this, rule| {
+            match rule.target {
+                Target::Name => &mut this.name,
+                Target::Message => &mut this.message,
+                Target::Row => &mut this.row,
+            }
+            .get_or_insert(rule.style);
+            this
+        })
+    }
+
+    // the rule replaces the user's color
+    fn apply_name(&self, string: &mut SpannedString<cursive::theme::Style>) {
+        if let Some(style) = self.name.or(self.row) {
+            for span in string.spans_attr_mut() {
+                *span.attr = span.attr.combine(style);
+            }
+        }
+    }
+
+    // keyword and mention styles are kept on top of the rule
+    fn apply_message(&self, string: &mut SpannedString<cursive::theme::Style>) {
+        if let Some(style) = self.message.or(self.row) {
+            let style = cursive::theme::Style::from(style);
+            for span in string.spans_attr_mut() {
+                *span.attr = style.combine(*span.attr);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cursive::{theme::Style as CursiveStyle, Vec2};
+
+    use super::*;
+
+    fn context(f: impl FnOnce(&mut Config)) -> Context {
+        let mut config = Config::default();
+        f(&mut config);
+        Context::new(config)
+    }
+
+    #[test]
+    fn styled_by_the_config_of_the_context() {
+        let entry = Entry::message("museun", "learning rust today");
+
+        let plain = context(|config| config.highlights.keywords.clear());
+        let keyword = context(|config| {
+            config.highlights.keywords = vec![Keyword::new("rust").style(Style {
+                fg: crate::Color::RED,
+                ..Style::default()
+            })]
+        });
+
+        let style_of = |context: &Context, word: &str| {
+            let message = entry.highlighted_message(context);
+            let style = message
+                .spans()
+                .find(|span| span.content == word)
+                .map(|span| *span.attr);
+            style.unwrap()
+        };
+        assert_eq!(style_of(&plain, "rust"), CursiveStyle::none());
+        assert_ne!(style_of(&keyword, "rust"), CursiveStyle::none());
+        assert_eq!(style_of(&keyword, "today"), CursiveStyle::none());
+    }
+
+    #[test]
+    fn laid_out_by_the_config_of_the_context() {
+        let entry = Entry::message("museun", "hello");
+        let shown = context(|config| config.timestamps = true);
+        let hidden = context(|config| config.timestamps = false);
+        let long = context(|config| config.timestamp_fmt = "%Y-%m-%d %H:%M:%S".into());
+
+        let width = |context: &Context| {
+            Entry::as_header_view(&entry, context)
+                .required_size(Vec2::new(10, 1))
+                .x
+        };
+        assert!(width(&hidden) < width(&shown));
+        assert!(width(&shown) < width(&long));
+    }
+}
@@ -0,0 +1,309 @@
+use std::io::{IsTerminal as _, Write};
+
+use cursive::{
+    theme::{BaseColor, Color, ColorType, Effect, Style},
+    utils::span::SpannedString,
+};
+
+use crate::{
+    config::IgnoreMode,
+    entry::Entry,
+    twitch::{Update, UpdateSink},
+    Config, Context,
+};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            s => anyhow::bail!("unknown format '{}', expected 'text' or 'json'", s),
+        }
+    }
+}
+
+/// Prints entries to stdout instead of the terminal ui
+///
+/// Status updates go to stderr so they don't end up in a pipe
+pub struct Headless {
+    format: Format,
+    ansi: bool,
+    out: Box<dyn Write + Send>,
+    context: Context,
+}
+
+impl Headless {
+    pub fn new(format: Format, context: Context) -> Self {
+        let out = std::io::stdout();
+        let ansi = format == Format::Text && out.is_terminal();
+        Self::with_output(format, ansi, context, Box::new(out))
+    }
+
+    fn with_output(
+        format: Format,
+        ansi: bool,
+        context: Context,
+        out: Box<dyn Write + Send>,
+    ) -> Self {
+        Self {
+            format,
+            ansi,
+            out,
+            context,
+        }
+    }
+
+    fn write_entry(&mut self, entry: &Entry) -> std::io::Result<()> {
+        let context = self.context.clone();
+        let config = context.config();
+        let (our_name, channel) = (context.our_name(), context.channel());
+        let (our_name, channel) = (our_name.as_deref(), channel.as_deref().unwrap_or_default());
+
+        if entry.is_ignored(&config.ignore) {
+            let line = match (config.ignore.mode, self.format) {
+                (IgnoreMode::Hide, _) => return Ok(()),
+                (IgnoreMode::Collapse, Format::Text) => {
+                    let name = SpannedString::styled(entry.name.trim(), entry.color);
+                    format!("{}: message hidden", self.render(&name))
+                }
+                // the stream stays one object per line, without the message
+                (IgnoreMode::Collapse, Format::Json) => {
+                    let mut json = entry.as_json(channel);
+                    json["message"] = serde_json::Value::Null;
+                    json["hidden"] = true.into();
+                    json.to_string()
+                }
+            };
+            return self.write_line(&line);
+        }
+
+        crate::overlay::publish(&context, entry);
+        context.stats().record(entry, &config);
+
+        let line = match self.format {
+            Format::Text => self.format_text(entry, channel, &config, our_name),
+            Format::Json => {
+                let mut json = entry.as_json(channel);
+                json["highlighted"] = entry.is_highlighted(&config.highlights, our_name).into();
+                json["mention"] = entry.mentions_us(&config.highlights, our_name).into();
+                json["hidden"] = false.into();
+                json.to_string()
+            }
+        };
+
+        self.write_line(&line)
+    }
+
+    fn format_text(
+        &self,
+        entry: &Entry,
+        channel: &str,
+        config: &Config,
+        our_name: Option<&str>,
+    ) -> String {
+        substitute(&config.output.template, |key| {
+            let value = match key {
+                "ts" => self.render(&entry.styled_timestamp(config)),
+                "channel" => channel.to_string(),
+                "name" => self.render(&entry.styled_name(config)),
+                "login" => entry.login.clone(),
+                "badge" => entry
+                    .badge
+                    .map(|badge| format!("{} ", self.render(&badge.as_spanned_string(config))))
+                    .unwrap_or_default(),
+                "message" => self.render(&entry.highlight_with(config, our_name)),
+                _ => return None,
+            };
+            Some(value)
+        })
+    }
+
+    fn render(&self, string: &SpannedString<Style>) -> String {
+        if !self.ansi {
+            return string.source().to_string();
+        }
+
+        string.spans().fold(String::new(), |mut out, span| {
+            let codes = ansi_codes(span.attr);
+            if codes.is_empty() {
+                out.push_str(span.content);
+            } else {
+                out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes, span.content));
+            }
+            out
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()
+    }
+}
+
+impl UpdateSink for Headless {
+    fn deliver(&mut self, update: Update) -> bool {
+        match update {
+            Update::Append(entry) => return self.write_entry(&entry).is_ok(),
+            Update::Connecting => eprintln!("connecting to Twitch..."),
+            Update::Connected(name) => {
+                self.context.set_our_name(name);
+                eprintln!("connected!")
+            }
+            Update::Joining(channel) => eprintln!("joining: {}", channel),
+            Update::Joined(channel) => {
+                self.context.set_channel(Some(&channel));
+                eprintln!("joined: {}", channel)
+            }
+            Update::Parting(channel) => eprintln!("leaving: {}", channel),
+            Update::Parted(channel) => {
+                self.context.set_channel(None);
+                eprintln!("left: {}", channel)
+            }
+            Update::Error(error) => eprintln!("error: {}", error),
+            Update::Disconnected(reason) => eprintln!("disconnected: {}", reason),
+            Update::Notice(notice) => eprintln!("notice: {}", notice.message),
+            Update::Raw(..)
+            | Update::Ping
+            | Update::Pong
+            | Update::RoomState(..)
+            | Update::Moderation(..) => {}
+        }
+        true
+    }
+}
+
+/// Replaces each `{key}` in the template, keeping unknown keys as they are
+fn substitute(template: &str, mut lookup: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest
+            .find('}')
+            .and_then(|end| lookup(&rest[1..end]).map(|value| (end, value)));
+
+        match value {
+            Some((end, value)) => {
+                out.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn ansi_codes(style: &Style) -> String {
+    fn color(color: ColorType, base: u8) -> Option<String> {
+        let code = match color {
+            ColorType::Color(Color::Rgb(r, g, b)) => format!("{};2;{};{};{}", base + 8, r, g, b),
+            ColorType::Color(Color::Dark(c)) => (base + base_color(c)).to_string(),
+            ColorType::Color(Color::Light(c)) => (base + 60 + base_color(c)).to_string(),
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    fn base_color(color: BaseColor) -> u8 {
+        match color {
+            BaseColor::Black => 0,
+            BaseColor::Red => 1,
+            BaseColor::Green => 2,
+            BaseColor::Yellow => 3,
+            BaseColor::Blue => 4,
+            BaseColor::Magenta => 5,
+            BaseColor::Cyan => 6,
+            BaseColor::White => 7,
+        }
+    }
+
+    let effects = style.effects.iter().filter_map(|effect| {
+        let code = match effect {
+            Effect::Bold => 1,
+            Effect::Italic => 3,
+            Effect::Underline => 4,
+            Effect::Blink => 5,
+            Effect::Reverse => 7,
+            Effect::Strikethrough => 9,
+            Effect::Simple => return None,
+        };
+        Some(code.to_string())
+    });
+
+    effects
+        .chain(color(style.color.front, 30))
+        .chain(color(style.color.back, 40))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_output_is_one_object_per_line() {
+        let mut config = Config::default();
+        config.ignore.users.push("ignored".into());
+        config.ignore.mode = IgnoreMode::Collapse;
+
+        let output = Output::default();
+        let mut headless = Headless::with_output(
+            Format::Json,
+            false,
+            Context::new(config),
+            Box::new(output.clone()),
+        );
+
+        for entry in [
+            Entry::message("someone", "hello"),
+            Entry::message("Ignored", "hidden away"),
+            Entry::message("someone", "with \"quotes\"\nand a newline"),
+        ] {
+            assert!(headless.deliver(Update::Append(entry)));
+        }
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let lines = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1]["hidden"], true);
+        assert_eq!(lines[1]["message"], serde_json::Value::Null);
+        assert_eq!(lines[2]["hidden"], false);
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead as _, BufReader, Write as _},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{Hook, Notify, Style},
+    entry::Entry,
+    twitch::{Update, UpdateSink},
+};
+
+/// What the hook wants done with a message
+#[derive(Debug, serde::Deserialize)]
+struct Reply {
+    id: u64,
+    #[serde(default)]
+    hide: bool,
+    #[serde(default)]
+    highlight: Option<Style>,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    notify: Option<Notify>,
+}
+
+struct Process {
+    child: Child,
+    lines: flume::Sender<String>,
+    replies: flume::Receiver<Reply>,
+}
+
+impl Process {
+    fn spawn(hook: &Hook) -> anyhow::Result<Self> {
+        // the child must not write to the terminal that cursive is drawing to
+        let mut child = Command::new(&hook.program)
+            .args(&hook.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let (mut stdin, stdout) = (
+            child.stdin.take().expect("stdin should be piped"),
+            child.stdout.take().expect("stdout should be piped"),
+        );
+
+        // writing happens elsewhere so a hook that doesn't read can't block us
+        let (lines, lines_rx) = flume::unbounded::<String>();
+        std::thread::spawn(move || {
+            for line in lines_rx {
+                if writeln!(stdin, "{}", line)
+                    .and_then(|_| stdin.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let (replies_tx, replies) = flume::unbounded();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                // anything that isn't a reply is just noise from the hook
+                if let Ok(reply) = serde_json::from_str(&line) {
+                    if replies_tx.send(reply).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            lines,
+            replies,
+        })
+    }
+
+    /// Sends the entry to the hook, waiting until the deadline for its reply
+    fn ask(
+        &mut self,
+        id: u64,
+        entry: &Entry,
+        channel: &str,
+        deadline: Instant,
+    ) -> anyhow::Result<Option<Reply>> {
+        if let Some(status) = self.child.try_wait()? {
+            anyhow::bail!("the hook has exited ({})", status)
+        }
+
+        let mut json = entry.as_json(channel);
+        json["id"] = id.into();
+        self.lines
+            .send(json.to_string())
+            .map_err(|_| anyhow::anyhow!("the hook has stopped reading"))?;
+
+        loop {
+            match self.replies.recv_deadline(deadline) {
+                Ok(reply) if reply.id == id => return Ok(Some(reply)),
+                // a late reply for an older message
+                Ok(..) => continue,
+                Err(flume::RecvTimeoutError::Timeout) => return Ok(None),
+                Err(flume::RecvTimeoutError::Disconnected) => {
+                    anyhow::bail!("the hook has exited")
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Passes each entry through the configured [`Hook`] before it reaches the sink
+///
+/// The hook is asked on a thread of its own, so the connection never waits on it
+pub struct Hooked<S> {
+    inner: Inner<S>,
+}
+
+enum Inner<S> {
+    Direct(S),
+    Background(Background),
+}
+
+impl<S> Hooked<S>
+where
+    S: UpdateSink,
+{
+    pub fn new(sink: S, hook: Option<Hook>) -> Self {
+        let inner = match hook {
+            Some(hook) => Inner::Background(Background::spawn(Worker::new(sink, hook))),
+            None => Inner::Direct(sink),
+        };
+        Self { inner }
+    }
+}
+
+impl<S> UpdateSink for Hooked<S>
+where
+    S: UpdateSink,
+{
+    fn deliver(&mut self, update: Update) -> bool {
+        match &mut self.inner {
+            Inner::Direct(sink) => sink.deliver(update),
+            Inner::Background(background) => background.send(update),
+        }
+    }
+}
+
+/// The worker's end of the queue
+struct Background {
+    updates: Option<flume::Sender<(Instant, Update)>>,
+    listening: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Background {
+    fn spawn<S: UpdateSink>(mut worker: Worker<S>) -> Self {
+        let (updates, updates_rx) = flume::unbounded();
+        let listening = Arc::new(AtomicBool::new(true));
+        let handle = std::thread::spawn({
+            let listening = Arc::clone(&listening);
+            move || {
+                for (received, update) in updates_rx {
+                    if !worker.deliver(received, update) {
+                        listening.store(false, Ordering::SeqCst);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            updates: Some(updates),
+            listening,
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, update: Update) -> bool {
+        self.listening.load(Ordering::SeqCst)
+            && self
+                .updates
+                .as_ref()
+                .is_some_and(|updates| updates.send((Instant::now(), update)).is_ok())
+    }
+}
+
+// what is still queued, like why the connection ended, is delivered before the connection finishes
+impl Drop for Background {
+    fn drop(&mut self) {
+        self.updates.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Worker<S> {
+    sink: S,
+    hook: Hook,
+    process: Option<Process>,
+    // when the process was started, for the restart limit
+    started: VecDeque<Instant>,
+    gave_up: bool,
+    channel: String,
+    id: u64,
+}
+
+impl<S> Worker<S>
+where
+    S: UpdateSink,
+{
+    /// How many times the hook can be started within the window, before it is given up on
+    const MAX_STARTS: usize = 3;
+    const START_WINDOW: Duration = Duration::from_secs(60);
+
+    fn new(sink: S, hook: Hook) -> Self {
+        Self {
+            sink,
+            hook,
+            process: None,
+            started: VecDeque::new(),
+            gave_up: false,
+            channel: String::new(),
+            id: 0,
+        }
+    }
+
+    fn deliver(&mut self, received: Instant, update: Update) -> bool {
+        let update = match update {
+            Update::Append(entry) => match self.apply(entry, received + self.hook.timeout()) {
+                Some(entry) => Update::Append(entry),
+                None => return true,
+            },
+            Update::Joined(channel) => {
+                self.channel = channel.clone();
+                Update::Joined(channel)
+            }
+            Update::Parted(channel) => {
+                self.channel.clear();
+                Update::Parted(channel)
+            }
+            update => update,
+        };
+        self.sink.deliver(update)
+    }
+
+    // the process is started lazily, and again after it exits, so its errors can be delivered to the sink
+    fn process(&mut self) -> Option<&mut Process> {
+        if self.process.is_none() && !self.gave_up {
+            let now = Instant::now();
+            while self
+                .started
+                .front()
+                .filter(|&&ts| now.duration_since(ts) >= Self::START_WINDOW)
+                .is_some()
+            {
+                self.started.pop_front();
+            }
+
+            if self.started.len() >= Self::MAX_STARTS {
+                self.gave_up = true;
+                let err = format!(
+                    "hook: {} exited {} times within {}s, it won't be started again",
+                    self.hook.program,
+                    Self::MAX_STARTS,
+                    Self::START_WINDOW.as_secs()
+                );
+                self.report(err);
+                return None;
+            }
+
+            self.started.push_back(now);
+            match Process::spawn(&self.hook) {
+                Ok(process) => self.process = Some(process),
+                Err(err) => {
+                    self.gave_up = true;
+                    let err = format!("hook: cannot start {}: {:#}", self.hook.program, err);
+                    self.report(err);
+                }
+            }
+        }
+        self.process.as_mut()
+    }
+
+    fn report(&mut self, error: String) {
+        let _ = self.sink.deliver(Update::Error(error));
+    }
+
+    /// Applies the hook's directives, returning `None` if the entry should be hidden
+    ///
+    /// An entry that is still waiting at the deadline is passed through as it is
+    fn apply(&mut self, mut entry: Entry, deadline: Instant) -> Option<Entry> {
+        // it waited its whole budget behind other entries
+        if Instant::now() >= deadline {
+            return Some(entry);
+        }
+
+        self.id += 1;
+        let (id, channel) = (self.id, self.channel.clone());
+        let result = match self.process() {
+            Some(process) => process.ask(id, &entry, &channel, deadline),
+            None => Ok(None),
+        };
+
+        let reply = match result {
+            Ok(Some(reply)) => reply,
+            Ok(None) => return Some(entry),
+            Err(err) => {
+                // it is started again for the next entry
+                self.process.take();
+                self.report(format!("hook: {:#}, restarting it", err));
+                return Some(entry);
+            }
+        };
+
+        if reply.hide {
+            return None;
+        }
+
+        entry.tags.extend(reply.tag);
+        entry.highlight = reply.highlight.or(entry.highlight);
+        entry.notify = reply.notify.or(entry.notify);
+        Some(entry)
+    }
+}
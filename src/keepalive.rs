@@ -0,0 +1,170 @@
+use std::time::{Duration, Instant};
+
+use crate::config;
+
+/// Where the keepalive gets the current time from
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// What the connection should do next to stay alive
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Wait for activity, for at most this long
+    Wait(Duration),
+    /// Send a PING
+    Ping,
+    /// Nothing arrived after the PING, it has been quiet for this long
+    TimedOut(Duration),
+}
+
+/// Decides when to send a PING, and when to give up on the connection
+///
+/// It is told about activity, and asked for the next [`Step`] whenever the connection has waited
+pub struct Keepalive<C = SystemClock> {
+    clock: C,
+    interval: Duration,
+    timeout: Duration,
+    last_activity: Instant,
+    pinged: Option<Instant>,
+}
+
+impl Keepalive {
+    pub fn new(config: config::Keepalive) -> Self {
+        Self::with_clock(SystemClock, config)
+    }
+}
+
+impl<C: Clock> Keepalive<C> {
+    pub fn with_clock(clock: C, config: config::Keepalive) -> Self {
+        Self {
+            last_activity: clock.now(),
+            clock,
+            interval: config.interval(),
+            timeout: config.timeout(),
+            pinged: None,
+        }
+    }
+
+    /// Records that something arrived from the server
+    pub fn activity(&mut self) {
+        self.last_activity = self.clock.now();
+        self.pinged.take();
+    }
+
+    /// How long it has been since something arrived from the server
+    pub fn quiet_for(&self) -> Duration {
+        self.clock
+            .now()
+            .saturating_duration_since(self.last_activity)
+    }
+
+    /// The next step, which is [`Step::Ping`] only once per quiet period
+    pub fn step(&mut self) -> Step {
+        let now = self.clock.now();
+        match self.pinged {
+            Some(pinged) => match now.saturating_duration_since(pinged) {
+                waited if waited >= self.timeout => Step::TimedOut(self.quiet_for()),
+                waited => Step::Wait(self.timeout - waited),
+            },
+            None => match now.saturating_duration_since(self.last_activity) {
+                quiet if quiet >= self.interval => {
+                    self.pinged.replace(now);
+                    Step::Ping
+                }
+                quiet => Step::Wait(self.interval - quiet),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct FakeClock(Rc<Cell<Instant>>);
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self(Rc::new(Cell::new(Instant::now())))
+        }
+
+        fn advance(&self, secs: u64) {
+            self.0.set(self.0.get() + Duration::from_secs(secs))
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn keepalive(clock: &FakeClock) -> Keepalive<FakeClock> {
+        let config = config::Keepalive {
+            ping_interval_secs: 10,
+            timeout_secs: 5,
+        };
+        Keepalive::with_clock(clock.clone(), config)
+    }
+
+    #[test]
+    fn waits_until_the_interval() {
+        let clock = FakeClock::new();
+        let mut keepalive = keepalive(&clock);
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(10)));
+
+        clock.advance(4);
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(6)));
+
+        // activity starts the interval over
+        clock.advance(4);
+        keepalive.activity();
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn pings_once_per_quiet_period() {
+        let clock = FakeClock::new();
+        let mut keepalive = keepalive(&clock);
+
+        clock.advance(10);
+        assert_eq!(keepalive.step(), Step::Ping);
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(5)));
+
+        clock.advance(2);
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(3)));
+
+        // the reply to the ping
+        keepalive.activity();
+        assert_eq!(keepalive.step(), Step::Wait(Duration::from_secs(10)));
+
+        clock.advance(10);
+        assert_eq!(keepalive.step(), Step::Ping);
+    }
+
+    #[test]
+    fn times_out_without_a_reply() {
+        let clock = FakeClock::new();
+        let mut keepalive = keepalive(&clock);
+
+        clock.advance(10);
+        assert_eq!(keepalive.step(), Step::Ping);
+
+        clock.advance(5);
+        assert_eq!(keepalive.step(), Step::TimedOut(Duration::from_secs(15)));
+        assert_eq!(keepalive.quiet_for(), Duration::from_secs(15));
+    }
+}
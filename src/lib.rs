@@ -1,23 +1,66 @@
+/// The name and version, e.g. `readchat2/0.3.0`
+pub const HEADER: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
 pub mod colors;
 
 pub mod panic_logger;
 mod twitch;
+#[cfg(feature = "tui")]
+pub use twitch::UiSink;
+pub use twitch::{Moderation, Notice, RoomState, Update, UpdateSink};
 
+#[cfg(feature = "tui")]
 mod app;
+#[cfg(feature = "tui")]
 pub use app::App;
 
+#[cfg(feature = "tui")]
 pub mod ui;
+#[cfg(feature = "tui")]
 pub use ui::{build_ui, OnView};
 
-mod state;
-pub use state::{get_config, get_config_mut, CONFIG};
+mod context;
+pub use context::Context;
 
-pub(crate) mod config;
-pub use config::{Action, Color, Config, Input};
+pub mod config;
+#[cfg(feature = "tui")]
+pub use config::Problem;
+pub use config::{Action, Color, Config, Highlights, Ignore, Input, Keepalive, Keyword, Rule};
 
 mod entry;
+pub use entry::{Badge, Entry, Part};
+
+#[cfg(feature = "tui")]
+mod notify;
+
+mod hook;
 
 mod simulated;
 
+pub mod keepalive;
+
 mod connect;
 pub use connect::ChatMode;
+
+mod client;
+pub use client::Client;
+
+#[cfg(all(unix, feature = "tui"))]
+pub mod control;
+
+#[cfg(feature = "tui")]
+pub mod overlay;
+
+#[cfg(feature = "tui")]
+pub mod stats;
+
+#[cfg(feature = "tui")]
+pub mod reload;
+
+#[cfg(feature = "tui")]
+mod poll;
+
+#[cfg(feature = "tui")]
+mod headless;
+#[cfg(feature = "tui")]
+pub use headless::{Format, Headless};
@@ -0,0 +1,102 @@
+use std::{
+    collections::VecDeque,
+    io::Write as _,
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use crate::{
+    config::{Notify, NotifyCommand, RateLimit},
+    entry::Entry,
+};
+
+/// Notifies about the entry, unless more than the rate limit were `sent` recently
+pub fn notify(
+    sent: &mut VecDeque<Instant>,
+    action: &Notify,
+    rate_limit: RateLimit,
+    entry: &Entry,
+    channel: &str,
+) -> anyhow::Result<()> {
+    if !allowed(sent, rate_limit) {
+        return Ok(());
+    }
+
+    let title = format!("{} in {}", entry.name.trim(), channel);
+    match action {
+        Notify::Bell => write_terminal("\x07"),
+        Notify::Osc9 => write_terminal(&format!(
+            "\x1b]9;{}: {}\x07",
+            sanitize(&title),
+            sanitize(&entry.data)
+        )),
+        Notify::Osc777 => write_terminal(&format!(
+            "\x1b]777;notify;{};{}\x07",
+            sanitize(&title).replace(';', ","),
+            sanitize(&entry.data)
+        )),
+        Notify::Command(cmd) => run_command(cmd, entry, channel),
+    }
+}
+
+fn allowed(sent: &mut VecDeque<Instant>, rate_limit: RateLimit) -> bool {
+    let window = rate_limit.window();
+    while sent.front().filter(|ts| ts.elapsed() >= window).is_some() {
+        sent.pop_front();
+    }
+
+    if sent.len() >= rate_limit.count {
+        return false;
+    }
+    sent.push_back(Instant::now());
+    true
+}
+
+// the terminal is only written to from the ui thread, between frames
+fn write_terminal(data: &str) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(data.as_bytes())?;
+    stdout.flush().map_err(Into::into)
+}
+
+fn run_command(cmd: &NotifyCommand, entry: &Entry, channel: &str) -> anyhow::Result<()> {
+    let name = entry.name.trim();
+    let args = cmd.args.iter().map(|arg| {
+        arg.replace("{name}", name)
+            .replace("{channel}", channel)
+            .replace("{message}", &entry.data)
+    });
+
+    let stdin = if cmd.json {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    };
+
+    // the child must not write to the terminal that cursive is drawing to
+    let mut child = Command::new(&cmd.program)
+        .args(args)
+        .stdin(stdin)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let json = cmd
+        .json
+        .then(|| serde_json::to_vec(&entry.as_json(channel)))
+        .transpose()?;
+
+    // a slow command shouldn't block the ui, so wait for it elsewhere
+    std::thread::spawn(move || {
+        if let (Some(json), Some(mut stdin)) = (json, child.stdin.take()) {
+            let _ = stdin.write_all(&json);
+        }
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+fn sanitize(input: &str) -> String {
+    input.chars().filter(|c| !c.is_control()).collect()
+}
@@ -0,0 +1,345 @@
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use cursive::{
+    theme::{BaseColor, Color, ColorType, Effect, Style},
+    utils::span::SpannedString,
+};
+
+use crate::{config::Overlay, entry::Entry, Context};
+
+const PAGE: &str = include_str!("../etc/overlay.html");
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Serves the overlay page and its event stream on localhost
+pub fn serve(overlay: Overlay, context: &Context) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, overlay.port))?;
+    let addr = listener.local_addr()?;
+
+    let context = context.clone();
+    let clients = Arc::new(AtomicUsize::new(0));
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // only this thread adds clients, so the count can't go over between the check and the add
+            if clients.load(Ordering::SeqCst) >= overlay.max_clients {
+                let _ = refuse(&stream);
+                continue;
+            }
+
+            let client = Client::new(&clients);
+            let context = context.clone();
+            let origin = overlay.allowed_origin.clone();
+            std::thread::spawn(move || {
+                let _client = client;
+                handle(stream, &context, origin.as_deref())
+            });
+        }
+    });
+
+    Ok(addr)
+}
+
+/// A connected client, counted until it is dropped
+struct Client(Arc<AtomicUsize>);
+
+impl Client {
+    fn new(clients: &Arc<AtomicUsize>) -> Self {
+        clients.fetch_add(1, Ordering::SeqCst);
+        Self(Arc::clone(clients))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Sends the entry to everything connected to the event stream
+pub(crate) fn publish(context: &Context, entry: &Entry) {
+    let mut subscribers = context.subscribers();
+    if subscribers.is_empty() {
+        return;
+    }
+
+    let event = {
+        let config = context.config();
+        let (our_name, channel) = (context.our_name(), context.channel());
+        let our_name = our_name.as_deref();
+        let mut json = entry.as_json(channel.as_deref().unwrap_or_default());
+        json["highlighted"] = entry.is_highlighted(&config.highlights, our_name).into();
+        json["mention"] = entry.mentions_us(&config.highlights, our_name).into();
+        json["name_spans"] = spans(&entry.styled_name(&config));
+        json["message_spans"] = spans(&entry.highlight_with(&config, our_name));
+        json.to_string()
+    };
+
+    subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+}
+
+fn refuse(mut stream: &TcpStream) -> std::io::Result<()> {
+    // this holds up the other connections, so the client doesn't get long to send its request
+    stream.set_read_timeout(Some(Duration::from_millis(100)))?;
+    let _ = read_request(stream);
+
+    write!(
+        stream,
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+}
+
+/// Reads the request line, skipping the headers
+fn read_request(stream: &TcpStream) -> std::io::Result<String> {
+    let mut request = String::new();
+    let mut reader = BufReader::new(stream);
+    reader.read_line(&mut request)?;
+
+    // the headers aren't needed, but they have to be read before replying
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    Ok(request)
+}
+
+fn handle(stream: TcpStream, context: &Context, origin: Option<&str>) -> anyhow::Result<()> {
+    // a client that never sends its request would otherwise keep its place
+    stream.set_read_timeout(Some(KEEPALIVE))?;
+    let request = read_request(&stream)?;
+
+    let mut stream = &stream;
+    match request.split_whitespace().nth(1) {
+        Some("/") => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: text/html; charset=utf-8\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            PAGE.len(),
+            PAGE
+        )?,
+
+        Some("/events") => {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/event-stream\r\n\
+                 Cache-Control: no-cache\r\n"
+            )?;
+            if let Some(origin) = origin {
+                write!(stream, "Access-Control-Allow-Origin: {}\r\n", origin)?;
+            }
+            write!(stream, "\r\n")?;
+
+            let (tx, rx) = flume::unbounded();
+            context.subscribers().push(tx);
+
+            loop {
+                match rx.recv_timeout(KEEPALIVE) {
+                    Ok(event) => write!(stream, "data: {}\n\n", event)?,
+                    // a comment keeps proxies from closing an idle stream, and notices closed ones
+                    Err(flume::RecvTimeoutError::Timeout) => write!(stream, ": keepalive\n\n")?,
+                    Err(flume::RecvTimeoutError::Disconnected) => break,
+                }
+                stream.flush()?;
+            }
+        }
+
+        _ => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )?,
+    }
+
+    stream.flush().map_err(Into::into)
+}
+
+/// The text of each span, with its style as css properties
+///
+/// Neighbouring spans with the same style are merged
+fn spans(string: &SpannedString<Style>) -> serde_json::Value {
+    string
+        .spans()
+        .fold(
+            Vec::<(String, serde_json::Value)>::new(),
+            |mut spans, span| {
+                let css = css(span.attr);
+                match spans.last_mut() {
+                    Some((text, last)) if *last == css => text.push_str(span.content),
+                    _ => spans.push((span.content.to_string(), css)),
+                }
+                spans
+            },
+        )
+        .into_iter()
+        .map(|(text, css)| serde_json::json!({ "text": text, "css": css }))
+        .collect()
+}
+
+fn css(style: &Style) -> serde_json::Value {
+    fn color(color: ColorType) -> Option<String> {
+        let name = |color| match color {
+            BaseColor::Black => "black",
+            BaseColor::Red => "red",
+            BaseColor::Green => "green",
+            BaseColor::Yellow => "yellow",
+            BaseColor::Blue => "blue",
+            BaseColor::Magenta => "magenta",
+            BaseColor::Cyan => "cyan",
+            BaseColor::White => "white",
+        };
+
+        let color = match color {
+            ColorType::Color(Color::Rgb(r, g, b)) => format!("#{:02X}{:02X}{:02X}", r, g, b),
+            ColorType::Color(Color::Dark(c)) => name(c).to_string(),
+            ColorType::Color(Color::Light(c)) => name(c).to_string(),
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    let mut css = serde_json::Map::new();
+    let mut insert = |key: &str, value: String| {
+        css.insert(key.to_string(), value.into());
+    };
+
+    if let Some(front) = color(style.color.front) {
+        insert("color", front)
+    }
+    if let Some(back) = color(style.color.back) {
+        insert("background-color", back)
+    }
+
+    let mut decorations = vec![];
+    for effect in style.effects.iter() {
+        match effect {
+            Effect::Bold => insert("font-weight", "bold".into()),
+            Effect::Italic => insert("font-style", "italic".into()),
+            Effect::Reverse => insert("filter", "invert(1)".into()),
+            Effect::Underline => decorations.push("underline"),
+            Effect::Strikethrough => decorations.push("line-through"),
+            Effect::Blink | Effect::Simple => {}
+        }
+    }
+    if !decorations.is_empty() {
+        insert("text-decoration", decorations.join(" "))
+    }
+
+    css.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use super::*;
+
+    fn start(overlay: Overlay) -> (SocketAddr, Context) {
+        let context = Context::default();
+        let overlay = Overlay {
+            enabled: true,
+            port: 0,
+            ..overlay
+        };
+        let addr = serve(overlay, &context).unwrap();
+        (addr, context)
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        stream
+    }
+
+    /// Connects to the event stream, returning its headers once it is subscribed
+    fn subscribe(addr: SocketAddr, context: &Context) -> (BufReader<TcpStream>, Vec<String>) {
+        let subscribers = context.subscribers().len();
+        let mut reader = BufReader::new(get(addr, "/events"));
+
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            headers.push(line.trim_end().to_string());
+        }
+
+        // the headers are sent before the subscriber is added
+        while context.subscribers().len() == subscribers {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        (reader, headers)
+    }
+
+    #[test]
+    fn serves_the_page() {
+        let (addr, _context) = start(Overlay::default());
+
+        let mut response = String::new();
+        get(addr, "/").read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(PAGE));
+
+        let mut response = String::new();
+        get(addr, "/nope").read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn streams_events() {
+        let (addr, context) = start(Overlay::default());
+
+        let (mut reader, headers) = subscribe(addr, &context);
+        assert_eq!(headers[0], "HTTP/1.1 200 OK");
+        assert!(headers
+            .iter()
+            .all(|header| !header.starts_with("Access-Control-Allow-Origin")));
+
+        for tx in context.subscribers().iter() {
+            tx.send("hello".into()).unwrap();
+        }
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "data: hello\n");
+    }
+
+    #[test]
+    fn allows_the_configured_origin() {
+        let (addr, context) = start(Overlay {
+            allowed_origin: Some("http://localhost:8080".into()),
+            ..Overlay::default()
+        });
+
+        let (_reader, headers) = subscribe(addr, &context);
+        assert!(headers
+            .iter()
+            .any(|header| header == "Access-Control-Allow-Origin: http://localhost:8080"));
+    }
+
+    #[test]
+    fn turns_away_clients_over_the_limit() {
+        let (addr, context) = start(Overlay {
+            max_clients: 1,
+            ..Overlay::default()
+        });
+
+        let _first = subscribe(addr, &context);
+
+        let mut response = String::new();
+        get(addr, "/").read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    }
+}
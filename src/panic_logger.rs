@@ -1,7 +1,47 @@
-pub fn setup() {
+use std::{
+    collections::VecDeque,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{Config, Context};
+
+/// How many of the last raw lines are kept for a crash report
+const RECENT_LINES: usize = 50;
+
+static RECENT: Lazy<Mutex<VecDeque<String>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LINES)));
+
+/// Parts of the configuration that can have personal details in them
+const REDACTED: &[&[&str]] = &[
+    &["hook", "args"],
+    &["notifications", "highlights", "command", "args"],
+    &["ignore", "users"],
+    &["control", "path"],
+];
+
+/// Keeps a raw line from the server for a crash report
+pub fn record_line(line: &str) {
+    let mut recent = RECENT.lock().unwrap();
+    if recent.len() == RECENT_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(line.to_string());
+}
+
+/// Writes a crash report to the data directory when something panics
+///
+/// With `tui`, the terminal is restored first and the process exits afterwards,
+/// as the ui cannot continue on a restored terminal
+pub fn setup(tui: bool, context: Context) {
     std::panic::set_hook(Box::new(move |info| {
         let backtrace = backtrace::Backtrace::new();
-        use std::io::Write as _;
+        if tui {
+            restore_terminal();
+        }
 
         let msg = info
             .payload()
@@ -10,25 +50,109 @@ pub fn setup() {
             .or_else(|| info.payload().downcast_ref().map(|s: &String| s.as_str()))
             .unwrap_or("Box<Any>");
 
-        let mut fi = std::fs::OpenOptions::new()
-            .create(true)
-            .append(false)
-            .truncate(true)
-            .write(true)
-            .open("panics.log")
-            .expect("open panics.log for writing");
-
-        match info.location() {
-            Some(loc) => {
-                writeln!(&mut fi, "{}:{}: {}", loc.file(), loc.line(), msg,)
-            }
-            None => {
-                writeln!(&mut fi, "{}", msg)
-            }
+        let msg = match info.location() {
+            Some(loc) => format!("{}:{}: {}", loc.file(), loc.line(), msg),
+            None => msg.to_string(),
+        };
+
+        let report = report(&msg, &backtrace, &context);
+        match write_report(&report) {
+            Ok(path) => eprintln!(
+                "{} crashed, a report was written to: {}",
+                crate::HEADER,
+                path.display()
+            ),
+            Err(err) => eprintln!(
+                "{} crashed: {}\ncannot write a report: {:#}",
+                crate::HEADER,
+                msg,
+                err
+            ),
         }
-        .unwrap();
 
-        writeln!(&mut fi).unwrap();
-        writeln!(&mut fi, "{:?}", backtrace).unwrap();
+        if tui {
+            std::process::exit(101)
+        }
     }));
 }
+
+fn report(msg: &str, backtrace: &backtrace::Backtrace, context: &Context) -> String {
+    let mut report = format!("{}\n\n{}\n\n{:?}\n", crate::HEADER, msg, backtrace);
+
+    report.push_str("\nconfiguration:\n");
+    report.push_str(&sanitized_config(context));
+
+    report.push_str("\nlast lines from the server:\n");
+    match RECENT.try_lock() {
+        Ok(recent) => recent.iter().for_each(|line| {
+            report.push_str(line.trim_end());
+            report.push('\n');
+        }),
+        Err(..) => report.push_str("(unavailable)\n"),
+    }
+    report
+}
+
+fn sanitized_config(context: &Context) -> String {
+    let value = match context.try_config() {
+        Some(config) => serde_yaml::to_value(&*config),
+        None => return "(locked)\n".to_string(),
+    };
+
+    let mut value = match value {
+        Ok(value) => value,
+        Err(err) => return format!("(cannot serialize it: {})\n", err),
+    };
+
+    for path in REDACTED {
+        redact(&mut value, path)
+    }
+    serde_yaml::to_string(&value).unwrap_or_else(|err| format!("(cannot serialize it: {})\n", err))
+}
+
+/// Replaces the value at the path, if there is one
+fn redact(value: &mut serde_yaml::Value, path: &[&str]) {
+    let node = path.iter().try_fold(value, |node, &key| node.get_mut(key));
+    if let Some(node) = node.filter(|node| !node.is_null()) {
+        *node = "<redacted>".into()
+    }
+}
+
+fn write_report(report: &str) -> anyhow::Result<PathBuf> {
+    let dir = Config::data_dir()
+        .map(|dir| dir.join("crashes"))
+        .unwrap_or_else(|_| std::env::temp_dir());
+    std::fs::create_dir_all(&dir)?;
+
+    let name = chrono::Local::now()
+        .format("crash-%Y%m%d-%H%M%S.log")
+        .to_string();
+    let path = dir.join(name);
+    write_file(&path, report)?;
+    Ok(path)
+}
+
+fn write_file(path: &Path, data: &str) -> std::io::Result<()> {
+    std::fs::File::create(path)?.write_all(data.as_bytes())
+}
+
+#[cfg(feature = "tui")]
+fn restore_terminal() {
+    use crossterm::{
+        cursor::Show,
+        event::DisableMouseCapture,
+        execute,
+        terminal::{disable_raw_mode, LeaveAlternateScreen},
+    };
+
+    let _ = execute!(
+        std::io::stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        Show
+    );
+    let _ = disable_raw_mode();
+}
+
+#[cfg(not(feature = "tui"))]
+fn restore_terminal() {}
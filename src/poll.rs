@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+
+use cursive::{theme::Effect, utils::span::SpannedString};
+
+use crate::{entry::Entry, ui::SpannedAppender as _};
+
+/// A tally of the votes for a set of options, counting one vote per user
+pub(crate) struct Poll {
+    options: Vec<String>,
+    // login -> index of the option
+    votes: HashMap<String, usize>,
+}
+
+impl Poll {
+    const DEFAULT_OPTIONS: [&'static str; 2] = ["1", "2"];
+    const BAR_WIDTH: usize = 30;
+
+    /// Options are separated by whitespace, defaulting to `1` and `2`
+    pub(crate) fn parse(input: &str) -> anyhow::Result<Self> {
+        let mut options = input
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        if options.is_empty() {
+            options = Self::DEFAULT_OPTIONS
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        }
+
+        let mut seen = HashSet::new();
+        options.retain(|option| seen.insert(option.clone()));
+        anyhow::ensure!(options.len() >= 2, "a poll needs at least two options");
+
+        Ok(Self {
+            options,
+            votes: HashMap::new(),
+        })
+    }
+
+    /// Counts the first word of the entry as a vote, if it is one of the options
+    ///
+    /// Returns whether the vote was counted
+    pub(crate) fn vote(&mut self, entry: &Entry) -> bool {
+        if self.votes.contains_key(&entry.login) {
+            return false;
+        }
+
+        let word = match entry.data.split_whitespace().next() {
+            Some(word) => word.trim_matches(|c: char| c.is_ascii_punctuation()),
+            None => return false,
+        };
+
+        match self
+            .options
+            .iter()
+            .position(|option| option.eq_ignore_ascii_case(word))
+        {
+            Some(index) => {
+                self.votes.insert(entry.login.clone(), index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn tally(&self) -> Vec<(&str, usize)> {
+        let mut counts = vec![0; self.options.len()];
+        for &index in self.votes.values() {
+            counts[index] += 1;
+        }
+        self.options
+            .iter()
+            .map(|s| s.as_str())
+            .zip(counts)
+            .collect()
+    }
+
+    fn percent(&self, count: usize) -> usize {
+        (count * 100)
+            .checked_div(self.votes.len())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn render(&self) -> SpannedString<cursive::theme::Style> {
+        let width = self.options.iter().map(|s| s.chars().count()).max();
+        let width = width.unwrap_or_default();
+        let total = self.votes.len();
+
+        let text = SpannedString::default().append(
+            format!(
+                "Poll: {} vote{}\n",
+                total,
+                if total == 1 { "" } else { "s" }
+            ),
+            Effect::Bold,
+        );
+
+        self.tally()
+            .into_iter()
+            .fold(text, |text, (option, count)| {
+                let filled = (count * Self::BAR_WIDTH)
+                    .checked_div(total)
+                    .unwrap_or_default();
+                text.append_plain(format!(
+                    "  {:<width$}  {}{}  {:>3}% ({})\n",
+                    option,
+                    "\u{2588}".repeat(filled),
+                    "\u{2591}".repeat(Self::BAR_WIDTH - filled),
+                    self.percent(count),
+                    count,
+                    width = width
+                ))
+            })
+    }
+
+    /// A single line describing the outcome
+    pub(crate) fn summary(&self) -> String {
+        let tally = self.tally();
+        let results = tally
+            .iter()
+            .map(|&(option, count)| format!("{}: {} ({}%)", option, count, self.percent(count)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let max = tally
+            .iter()
+            .map(|&(_, count)| count)
+            .max()
+            .unwrap_or_default();
+        let winners = tally
+            .iter()
+            .filter(|&&(_, count)| count == max && max > 0)
+            .map(|&(option, _)| option)
+            .collect::<Vec<_>>();
+
+        match winners.as_slice() {
+            [] => format!("{} -- no votes", results),
+            [winner] => format!("{} -- {} wins", results, winner),
+            tied => format!("{} -- tie between {}", results, tied.join(", ")),
+        }
+    }
+}
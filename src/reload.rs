@@ -0,0 +1,40 @@
+use std::{path::PathBuf, time::SystemTime};
+
+use cursive::CbSink;
+
+use crate::{App, Config, Context};
+
+const INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Reloads the configuration when its files change
+///
+/// This watches `config.yaml` and the override file of the current channel
+pub fn watch(sink: CbSink, context: Context) {
+    std::thread::spawn(move || {
+        let mut last = snapshot(&context);
+        loop {
+            std::thread::sleep(INTERVAL);
+
+            let next = snapshot(&context);
+            if next != last && sink.send(Box::new(App::reload_config)).is_err() {
+                break;
+            }
+            last = next;
+        }
+    });
+}
+
+/// The files that exist, with when they were last modified
+fn snapshot(context: &Context) -> Vec<(PathBuf, SystemTime)> {
+    let channel = context.channel();
+    let channel = channel.as_deref().map(Config::channel_config_path);
+
+    std::iter::once(Config::config_path())
+        .chain(channel)
+        .flatten()
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|md| md.modified());
+            modified.ok().map(|modified| (path, modified))
+        })
+        .collect()
+}
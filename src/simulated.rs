@@ -2,10 +2,10 @@ use std::{
     collections::HashSet,
     io::{BufRead as _, BufReader, Write},
     net::{SocketAddr, TcpListener, TcpStream},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
-type Color = cursive::theme::Color;
+use crate::Color;
 
 const IPSUM: [&str; 89] = include!("../etc/ipsum.inc");
 const ANIMALS: [&str; 25] = include!("../etc/animals.inc");
@@ -39,12 +39,8 @@ impl Chatter {
         );
 
         let color = crate::colors::DEFAULT_COLORS.choose().copied().unwrap();
-        let display_color = match color {
-            Color::Rgb(r, g, b) => {
-                format!("#{r:02X}{g:02X}{b:02X}", r = r, g = g, b = b)
-            }
-            _ => unreachable!(),
-        };
+        let Color(r, g, b) = color;
+        let display_color = format!("#{r:02X}{g:02X}{b:02X}", r = r, g = g, b = b);
 
         Self {
             name: name.into(),
@@ -73,24 +69,59 @@ impl Chatter {
     }
 }
 
-fn garbage_out(io: &mut dyn Write, chatters: &[Chatter]) -> anyhow::Result<()> {
+// the channel everyone is chatting in, if we've joined one
+type Joined = Arc<Mutex<Option<String>>>;
+
+fn garbage_out(mut io: &TcpStream, chatters: &[Chatter], joined: &Joined) -> anyhow::Result<()> {
     const MIN: u64 = 250;
     const MAX: u64 = 1500;
 
     while let Some(chatter) = chatters.choose() {
-        write!(
-            io,
-            "@color={color} :{name}!{name}@{name} PRIVMSG #testing :{msg}\r\n",
-            color = chatter.display_color,
-            name = chatter.name,
-            msg = chatter.speak()
-        )?;
+        // the lock is held so lines written by the other side of the connection aren't split
+        if let Some(channel) = &*joined.lock().unwrap() {
+            io.write_all(
+                format!(
+                    "@color={color} :{name}!{name}@{name} PRIVMSG {channel} :{msg}\r\n",
+                    color = chatter.display_color,
+                    name = chatter.name,
+                    channel = channel,
+                    msg = chatter.speak()
+                )
+                .as_bytes(),
+            )?;
+        }
         std::thread::sleep(std::time::Duration::from_millis(fastrand::u64(MIN..MAX)))
     }
 
     Ok(())
 }
 
+/// Answers the joins and parts sent after the first join
+fn follow_channels(io: &TcpStream, joined: &Joined) -> anyhow::Result<()> {
+    const JOIN_MESSAGE: &str = include!("../etc/join.inc");
+
+    for line in BufReader::new(io).lines() {
+        let line = line?;
+        let (command, channel) = match line.split_once(' ') {
+            Some((command @ ("JOIN" | "PART"), channel)) => (command, channel),
+            _ => continue,
+        };
+
+        let mut joined = joined.lock().unwrap();
+        let reply = JOIN_MESSAGE
+            .replace("JOIN", command)
+            .replace("#testing", channel);
+        (&*io).write_all(reply.as_bytes())?;
+
+        match command {
+            "JOIN" => joined.replace(channel.to_string()),
+            _ => joined.take(),
+        };
+    }
+
+    Ok(())
+}
+
 fn twitch_chat_experience() -> anyhow::Result<SocketAddr> {
     let cap = fastrand::usize(5..15);
     let mut chatters = Vec::with_capacity(cap);
@@ -111,12 +142,22 @@ fn twitch_chat_experience() -> anyhow::Result<SocketAddr> {
 }
 
 fn feed_chat(listener: TcpListener, chatters: Vec<Chatter>) {
-    for mut socket in listener.incoming().flatten() {
+    for socket in listener.incoming().flatten() {
         if wait_for_join(&socket).is_err() {
             continue;
         }
 
-        if garbage_out(&mut socket, &chatters).is_err() {
+        let joined = Joined::new(Mutex::new(Some("#testing".to_string())));
+        let reader = match socket.try_clone() {
+            Ok(reader) => reader,
+            Err(..) => continue,
+        };
+        std::thread::spawn({
+            let joined = Arc::clone(&joined);
+            move || follow_channels(&reader, &joined)
+        });
+
+        if garbage_out(&socket, &chatters, &joined).is_err() {
             continue;
         }
     }
@@ -130,7 +171,7 @@ fn wait_for_join(mut io: &TcpStream) -> anyhow::Result<()> {
         io.write_all(line.as_bytes())?;
     }
 
-    for line in BufReader::new(io).lines().flatten() {
+    for line in BufReader::new(io).lines().map_while(Result::ok) {
         if line == "JOIN #testing" {
             io.write_all(JOIN_MESSAGE.as_bytes())?;
             break;
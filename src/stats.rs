@@ -0,0 +1,231 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Local, TimeZone as _};
+use cursive::{theme::Effect, utils::span::SpannedString};
+
+use crate::{entry::Entry, entry::Part, ui::SpannedAppender as _, Config, Context};
+
+/// Running statistics for this session
+pub(crate) struct Stats {
+    started: DateTime<Local>,
+    total: usize,
+    // keyed by minutes since the epoch
+    per_minute: BTreeMap<i64, usize>,
+    chatters: HashMap<String, usize>,
+    words: HashMap<String, usize>,
+    emotes: HashMap<String, usize>,
+    keywords: HashMap<String, usize>,
+    domains: HashMap<String, usize>,
+}
+
+impl Stats {
+    const TOP: usize = 10;
+    const EXPORT_TOP: usize = 100;
+    const SPARKLINE_MINUTES: i64 = 30;
+    // shorter words are mostly filler
+    const MIN_WORD_LEN: usize = 4;
+
+    fn new() -> Self {
+        Self {
+            started: Local::now(),
+            total: 0,
+            per_minute: BTreeMap::new(),
+            chatters: HashMap::new(),
+            words: HashMap::new(),
+            emotes: HashMap::new(),
+            keywords: HashMap::new(),
+            domains: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, entry: &Entry, config: &Config) {
+        fn count(map: &mut HashMap<String, usize>, key: impl Into<String>) {
+            *map.entry(key.into()).or_default() += 1
+        }
+
+        self.total += 1;
+        *self.per_minute.entry(minute(entry.ts)).or_default() += 1;
+        count(&mut self.chatters, entry.name.trim());
+
+        for emote in &entry.emotes {
+            count(&mut self.emotes, emote)
+        }
+
+        for part in entry.find_keywords(&config.highlights.keywords) {
+            if let Part::Matched(name, ..) = part {
+                count(&mut self.keywords, name.to_lowercase())
+            }
+        }
+
+        for link in entry.find_links() {
+            if let Some(domain) = url::Url::parse(&link).ok().and_then(|url| {
+                url.host_str()
+                    .map(|host| host.trim_start_matches("www.").to_string())
+            }) {
+                count(&mut self.domains, domain)
+            }
+        }
+
+        entry
+            .data
+            .split_whitespace()
+            .filter(|word| !word.starts_with('@') && url::Url::parse(word).is_err())
+            .filter(|word| !entry.emotes.iter().any(|emote| emote == word))
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|word| word.chars().count() >= Self::MIN_WORD_LEN)
+            .for_each(|word| count(&mut self.words, word.to_lowercase()));
+    }
+
+    /// Messages per minute for the last few minutes, oldest first
+    fn recent_rate(&self) -> Vec<usize> {
+        let now = minute(Local::now());
+        let start = (now - Self::SPARKLINE_MINUTES + 1).max(minute(self.started));
+        (start..=now)
+            .map(|minute| self.per_minute.get(&minute).copied().unwrap_or_default())
+            .collect()
+    }
+
+    pub(crate) fn render(&self) -> SpannedString<cursive::theme::Style> {
+        fn section(
+            text: SpannedString<cursive::theme::Style>,
+            title: &str,
+            items: Vec<(&str, usize)>,
+        ) -> SpannedString<cursive::theme::Style> {
+            let text = text.append(format!("\n{}\n", title), Effect::Bold);
+            if items.is_empty() {
+                return text.append("  none yet\n", Effect::Italic);
+            }
+
+            let width = items.iter().map(|(k, _)| k.chars().count()).max();
+            items.into_iter().fold(text, |text, (key, count)| {
+                text.append_plain(format!(
+                    "  {:<width$}  {}\n",
+                    key,
+                    count,
+                    width = width.unwrap_or_default()
+                ))
+            })
+        }
+
+        let rate = self.recent_rate();
+        let peak = rate.iter().copied().max().unwrap_or_default();
+        let elapsed = Local::now() - self.started;
+
+        let text = SpannedString::default()
+            .append("Session\n", Effect::Bold)
+            .append_plain(format!(
+                "  started {}, {} minutes ago\n  {} messages from {} unique chatters\n",
+                self.started.format("%X"),
+                elapsed.num_minutes(),
+                self.total,
+                self.chatters.len(),
+            ))
+            .append(
+                format!(
+                    "\nMessages per minute (last {} minutes, peak {})\n",
+                    rate.len(),
+                    peak
+                ),
+                Effect::Bold,
+            )
+            .append_plain(format!("  {}\n", sparkline(&rate)));
+
+        <_>::into_iter([
+            ("Top chatters", &self.chatters),
+            ("Words", &self.words),
+            ("Emotes", &self.emotes),
+            ("Keyword hits", &self.keywords),
+            ("Link domains", &self.domains),
+        ])
+        .fold(text, |text, (title, map)| {
+            section(text, title, top(map, Self::TOP))
+        })
+    }
+
+    pub(crate) fn as_json(&self, channel: &str) -> serde_json::Value {
+        let top = |map| {
+            top(map, Self::EXPORT_TOP)
+                .into_iter()
+                .map(|(name, count)| serde_json::json!({ "name": name, "count": count }))
+                .collect::<Vec<_>>()
+        };
+
+        let per_minute = self
+            .per_minute
+            .iter()
+            .map(|(&minute, &count)| {
+                let ts = Local.timestamp_opt(minute * 60, 0).single();
+                serde_json::json!({ "minute": ts.map(|ts| ts.to_rfc3339()), "count": count })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "channel": channel,
+            "started": self.started.to_rfc3339(),
+            "ended": Local::now().to_rfc3339(),
+            "messages": self.total,
+            "unique_chatters": self.chatters.len(),
+            "per_minute": per_minute,
+            "top_chatters": top(&self.chatters),
+            "words": top(&self.words),
+            "emotes": top(&self.emotes),
+            "keywords": top(&self.keywords),
+            "domains": top(&self.domains),
+        })
+    }
+}
+
+// a session's stats start when it is created
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes the statistics for this session to the data directory, returning the path
+pub fn export(context: &Context) -> anyhow::Result<PathBuf> {
+    let channel = context.channel();
+    let channel = channel.as_deref().unwrap_or_default();
+
+    let stats = context.stats();
+    let name = format!(
+        "stats-{}-{}.json",
+        channel.trim_start_matches('#'),
+        stats.started.format("%Y%m%d-%H%M%S")
+    );
+
+    let dir = Config::data_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(name);
+
+    let data = serde_json::to_vec_pretty(&stats.as_json(channel))?;
+    std::fs::write(&path, data)?;
+    Ok(path)
+}
+
+fn minute(ts: DateTime<Local>) -> i64 {
+    ts.timestamp().div_euclid(60)
+}
+
+fn top(map: &HashMap<String, usize>, n: usize) -> Vec<(&str, usize)> {
+    let mut items = map
+        .iter()
+        .map(|(k, &v)| (k.as_str(), v))
+        .collect::<Vec<_>>();
+    items.sort_unstable_by(|(lk, lv), (rk, rv)| rv.cmp(lv).then_with(|| lk.cmp(rk)));
+    items.truncate(n);
+    items
+}
+
+fn sparkline(values: &[usize]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = values.iter().copied().max().unwrap_or_default().max(1);
+    values
+        .iter()
+        .map(|&value| BARS[value * (BARS.len() - 1) / max])
+        .collect()
+}
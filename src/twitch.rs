@@ -2,7 +2,7 @@ use std::{
     io::{Read, Write},
     net::TcpStream,
     ops::Deref,
-    time::{Duration, Instant, SystemTime},
+    time::SystemTime,
 };
 
 use twitchchat::{
@@ -11,7 +11,18 @@ use twitchchat::{
     UserConfig, {Decoder, Encoder, FromIrcMessage as _},
 };
 
-use crate::{app::App, entry::Entry};
+#[cfg(feature = "tui")]
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "tui")]
+use cursive::CbSink;
+
+#[cfg(feature = "tui")]
+use crate::app::App;
+use crate::{
+    entry::Entry,
+    keepalive::{self, Clock, Keepalive},
+};
 
 pub enum Activity {
     Pong,
@@ -19,16 +30,132 @@ pub enum Activity {
     Message,
 }
 
+// entries are the common case, so they aren't boxed
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum Update {
     Raw(String),
     Append(Entry),
     Connecting,
-    Connected,
+    Connected(String),
     Ping,
     Pong,
     Joining(String),
     Joined(String),
+    Parting(String),
+    Parted(String),
+    RoomState(RoomState),
+    Notice(Notice),
+    Moderation(Moderation),
+    Error(String),
+    Disconnected(String),
+}
+
+/// The modes of a channel
+///
+/// All of them are sent when the channel is joined, afterwards only the ones that changed are
+#[derive(Clone, Debug, Default)]
+pub struct RoomState {
+    pub channel: String,
+    pub emote_only: Option<bool>,
+    /// How many minutes someone has to follow for before they can chat, `-1` when it is off
+    pub followers_only: Option<i64>,
+    pub r9k: Option<bool>,
+    /// How many seconds someone has to wait between messages, `0` when it is off
+    pub slow: Option<u64>,
+    pub subs_only: Option<bool>,
+}
+
+/// A notice from the server, e.g. that a mode was changed or that something was refused
+#[derive(Clone, Debug)]
+pub struct Notice {
+    /// The channel, or `*` for notices about the connection
+    pub channel: String,
+    /// The `msg-id`, see <https://dev.twitch.tv/docs/irc/msg-id>
+    pub id: Option<String>,
+    pub message: String,
+}
+
+/// Messages that were removed by a moderator
+#[derive(Clone, Debug)]
+pub enum Moderation {
+    /// The whole chat was cleared
+    Cleared { channel: String },
+    /// Everything from a user was removed, and they were banned
+    Banned { channel: String, login: String },
+    /// Everything from a user was removed, and they can't chat for `secs` seconds
+    TimedOut {
+        channel: String,
+        login: String,
+        secs: u64,
+    },
+    /// A single message was removed
+    Deleted {
+        channel: String,
+        login: Option<String>,
+        message: Option<String>,
+    },
+}
+
+/// Something asked of the connection while it is running
+#[derive(Debug)]
+pub enum Request {
+    Join(String),
+    Part(String),
+}
+
+/// Where the updates read from the connection are delivered to
+pub trait UpdateSink: Send + 'static {
+    /// Delivers an update, returning `false` once nobody is listening anymore
+    fn deliver(&mut self, update: Update) -> bool;
+}
+
+impl UpdateSink for flume::Sender<Update> {
+    fn deliver(&mut self, update: Update) -> bool {
+        self.send(update).is_ok()
+    }
+}
+
+/// Delivers updates to the terminal ui
+///
+/// Updates are batched: at most one callback is queued at a time, and it applies
+/// everything that arrived since the ui last got to it
+#[cfg(feature = "tui")]
+pub struct UiSink {
+    sink: CbSink,
+    pending: Arc<Mutex<Vec<Update>>>,
+}
+
+#[cfg(feature = "tui")]
+impl UiSink {
+    pub fn new(sink: CbSink) -> Self {
+        Self {
+            sink,
+            pending: Arc::default(),
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl UpdateSink for UiSink {
+    fn deliver(&mut self, update: Update) -> bool {
+        let queued = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push(update);
+            pending.len() > 1
+        };
+        if queued {
+            return true;
+        }
+
+        let pending = Arc::clone(&self.pending);
+        self.sink
+            .send(Box::new(move |cursive| {
+                let updates = std::mem::take(&mut *pending.lock().unwrap());
+                App::apply_updates(cursive, updates)
+            }))
+            .is_ok()
+    }
 }
 
 pub fn read_loop<I, R>(
@@ -44,47 +171,88 @@ where
     let decoder = Decoder::new(&*stream);
     let mut encoder = Encoder::new(&*stream);
     let mut our_name = String::new();
+    // messages still arriving from a channel we've left are dropped
+    let mut joined = None;
 
-    for message in decoder
-        .into_iter()
-        .flatten()
-        .map(Commands::from_irc)
-        .flatten()
-    {
+    for message in decoder.into_iter().flatten().flat_map(Commands::from_irc) {
+        crate::panic_logger::record_line(message.raw());
         updates.send(Update::Raw(message.raw().to_string()))?;
 
+        // anything from the server counts as activity for the keepalive
+        activity.send(match &message {
+            Ping(msg) => Activity::Ping(msg.token().to_string()),
+            Pong(_) => Activity::Pong,
+            _ => Activity::Message,
+        })?;
+
         match message {
             Ready(msg) => {
-                updates.send(Update::Connected)?;
                 our_name = msg.username().to_string();
+                updates.send(Update::Connected(our_name.clone()))?;
 
                 updates.send(Update::Joining(channel.to_string()))?;
                 encoder.encode(join(channel))?;
-
-                activity.send(Activity::Message)?;
             }
 
             Join(msg) if our_name == msg.name() => {
-                updates.send(Update::Joined(channel.to_string()))?;
-                activity.send(Activity::Message)?;
+                joined.replace(msg.channel().to_string());
+                updates.send(Update::Joined(msg.channel().to_string()))?;
+            }
+
+            Part(msg) if our_name == msg.name() => {
+                joined.take();
+                updates.send(Update::Parted(msg.channel().to_string()))?;
             }
 
-            Privmsg(msg) => {
+            Privmsg(msg) if joined.as_deref() == Some(msg.channel()) => {
                 updates.send(Update::Append(msg.into()))?;
-                activity.send(Activity::Message)?;
             }
 
-            Ping(msg) => {
-                updates.send(Update::Ping)?;
-                activity.send(Activity::Ping(msg.token().to_string()))?;
+            Ping(_) => updates.send(Update::Ping)?,
+            Pong(_) => updates.send(Update::Pong)?,
+
+            Commands::RoomState(msg) if joined.as_deref() == Some(msg.channel()) => {
+                let flag = |key| msg.tags().get(key).map(|value| value == "1");
+                updates.send(Update::RoomState(RoomState {
+                    channel: msg.channel().to_string(),
+                    emote_only: flag("emote-only"),
+                    followers_only: msg.tags().get_parsed("followers-only"),
+                    r9k: flag("r9k"),
+                    slow: msg.tags().get_parsed("slow"),
+                    subs_only: flag("subs-only"),
+                }))?;
             }
-            Pong(_) => {
-                updates.send(Update::Pong)?;
-                activity.send(Activity::Pong)?;
+
+            Commands::Notice(msg) => updates.send(Update::Notice(Notice {
+                channel: msg.channel().to_string(),
+                id: msg.tags().get("msg-id").map(ToString::to_string),
+                message: msg.message().to_string(),
+            }))?,
+
+            ClearChat(msg) if joined.as_deref() == Some(msg.channel()) => {
+                let channel = msg.channel().to_string();
+                let moderation = match (msg.name(), msg.ban_duration()) {
+                    (None, ..) => Moderation::Cleared { channel },
+                    (Some(login), None) => Moderation::Banned {
+                        channel,
+                        login: login.to_string(),
+                    },
+                    (Some(login), Some(secs)) => Moderation::TimedOut {
+                        channel,
+                        login: login.to_string(),
+                        secs,
+                    },
+                };
+                updates.send(Update::Moderation(moderation))?;
             }
 
-            ClearChat(_) => {}
-            ClearMsg(_) => {}
+            ClearMsg(msg) if joined.as_deref() == Some(msg.channel()) => {
+                updates.send(Update::Moderation(Moderation::Deleted {
+                    channel: msg.channel().to_string(),
+                    login: msg.login().map(ToString::to_string),
+                    message: msg.message().map(ToString::to_string),
+                }))?;
+            }
 
             HostTarget(_) => {}
             Reconnect(_) => {}
@@ -109,100 +277,189 @@ pub fn connect() -> anyhow::Result<TcpStream> {
         .map_err(Into::into)
 }
 
+/// Delivers updates from the reader to the sink, and keeps the connection alive
+///
+/// Returns `true` if the reader finished, or `false` if the sink stopped listening
 pub fn inner_loop(
     mut encoder: twitchchat::Encoder<&TcpStream>,
     updates_rx: flume::Receiver<Update>,
     activity_rx: flume::Receiver<Activity>,
-    sink: cursive::CbSink,
+    requests_rx: flume::Receiver<Request>,
+    mut keepalive: Keepalive<impl Clock>,
+    sink: &mut impl UpdateSink,
     mut logger: impl Write + Send + Sync + 'static,
-) -> anyhow::Result<()> {
-    const WINDOW: Duration = Duration::from_secs(15);
-    const TIMEOUT: Duration = Duration::from_secs(30);
-
+) -> anyhow::Result<bool> {
     enum Step {
         Continue,
-        Exit,
+        Request(Request),
+        Closed,
+        Stop,
+        Failed(anyhow::Error),
     }
 
-    let ts = SystemTime::UNIX_EPOCH
-        .elapsed()
-        .map(|dur| dur.as_millis().to_string())
-        .unwrap();
-    encoder.encode(ping(&ts)).unwrap();
-
-    let mut last = Instant::now();
-
     loop {
-        std::thread::yield_now();
+        let wait = match keepalive.step() {
+            keepalive::Step::Wait(wait) => wait,
+            keepalive::Step::Ping => {
+                let ts = SystemTime::UNIX_EPOCH
+                    .elapsed()
+                    .map(|dur| dur.as_millis().to_string())?;
+                encoder.encode(ping(&ts))?;
+                continue;
+            }
+            keepalive::Step::TimedOut(quiet) => {
+                encoder.encode(raw("QUIT :leaving"))?;
+                anyhow::bail!(
+                    "timed out, nothing was heard from twitch for {}s",
+                    quiet.as_secs()
+                );
+            }
+        };
 
-        match flume::Selector::new()
+        let step = flume::Selector::new()
             .recv(&activity_rx, |activity| match activity {
-                Ok(Activity::Ping(tok)) => {
-                    let _ = encoder.encode(pong(&tok));
-                    last = Instant::now();
-                    Step::Continue
-                }
-                Ok(..) => {
-                    last = Instant::now();
-                    Step::Continue
+                Ok(activity) => {
+                    keepalive.activity();
+                    match activity {
+                        Activity::Ping(tok) => match encoder.encode(pong(&tok)) {
+                            Ok(..) => Step::Continue,
+                            Err(err) => Step::Failed(err.into()),
+                        },
+                        Activity::Pong | Activity::Message => Step::Continue,
+                    }
                 }
-                Err(..) => Step::Exit,
+                Err(..) => Step::Closed,
+            })
+            .recv(&requests_rx, |request| match request {
+                Ok(request) => Step::Request(request),
+                Err(..) => Step::Stop,
             })
             .recv(&updates_rx, |update| match update {
                 Ok(update) => {
-                    let cb: Box<dyn FnOnce(&mut cursive::Cursive) + Send> = match update {
-                        Update::Raw(raw) => {
-                            if logger
-                                .write_all(raw.as_bytes())
-                                .and_then(|_| logger.flush())
-                                .is_err()
-                            {
-                                return Step::Exit;
-                            }
-                            Box::new(|c| App::append_raw(c, raw))
+                    if let Update::Raw(raw) = &update {
+                        if let Err(err) = logger
+                            .write_all(raw.as_bytes())
+                            .and_then(|_| logger.flush())
+                        {
+                            return Step::Failed(anyhow::anyhow!(
+                                "cannot write the transcript: {}",
+                                err
+                            ));
                         }
-                        Update::Append(entry) => Box::new(|c| App::append_entry(c, entry)),
-                        Update::Connecting => Box::new(App::on_connecting),
-                        Update::Connected => Box::new(App::on_connected),
-                        Update::Ping => Box::new(App::on_ping),
-                        Update::Pong => Box::new(App::on_pong),
-                        Update::Joining(channel) => Box::new(|c| App::on_joining(c, channel)),
-                        Update::Joined(channel) => Box::new(|c| App::on_joined(c, channel)),
-                    };
-                    if sink.send(cb).is_ok() {
+                    }
+                    if sink.deliver(update) {
                         Step::Continue
                     } else {
-                        Step::Exit
+                        Step::Stop
                     }
                 }
-                Err(_) => Step::Exit,
+                Err(_) => Step::Closed,
             })
-            .wait_timeout(TIMEOUT)
-        {
-            Ok(step) => match step {
-                Step::Continue => continue,
-                Step::Exit => break,
-            },
-            Err(_) => {
-                match last.elapsed() {
-                    dur if dur >= WINDOW => {
-                        let ts = SystemTime::UNIX_EPOCH
-                            .elapsed()
-                            .map(|dur| dur.as_millis().to_string())?;
-                        encoder.encode(ping(&ts))?;
-                        // TODO don't do this
-                        std::thread::sleep(std::time::Duration::from_millis(150));
-                        // give the network some time to do something
+            .wait_timeout(wait);
+
+        // waiting the whole time is left to the keepalive
+        let step = match step {
+            Ok(step) => step,
+            Err(..) => continue,
+        };
+
+        match step {
+            Step::Continue => {}
+            Step::Request(request) => {
+                let update = match request {
+                    Request::Join(channel) => {
+                        encoder.encode(join(&channel))?;
+                        Update::Joining(channel)
                     }
-                    dur if dur >= TIMEOUT => {
-                        encoder.encode(raw("QUIT :leaving"))?;
-                        anyhow::bail!("we've timed out");
+                    Request::Part(channel) => {
+                        encoder.encode(part(&channel))?;
+                        Update::Parting(channel)
                     }
-                    _ => {}
+                };
+                if !sink.deliver(update) {
+                    return Ok(false);
                 }
             }
+            Step::Closed => return Ok(true),
+            Step::Stop => return Ok(false),
+            Step::Failed(err) => return Err(err),
         }
     }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead as _, BufReader},
+        net::TcpListener,
+        sync::Arc,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+
+    /// A client running the loops against a listener we play the server on
+    fn start() -> (BufReader<TcpStream>, JoinHandle<anyhow::Result<bool>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Arc::new(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+        let (server, _) = listener.accept().unwrap();
+
+        let (updates_tx, updates_rx) = flume::unbounded();
+        let (activity_tx, activity_rx) = flume::unbounded();
+        let (requests_tx, requests_rx) = flume::unbounded();
+
+        std::thread::spawn({
+            let client = Arc::clone(&client);
+            move || read_loop(client, "#test", updates_tx, activity_tx)
+        });
+
+        let handle = std::thread::spawn(move || {
+            let keepalive = crate::config::Keepalive {
+                ping_interval_secs: 1,
+                timeout_secs: 1,
+            };
+            // kept alive so the loop isn't told to stop
+            let _requests_tx = requests_tx;
+            let (mut sink, _updates) = flume::unbounded();
+            inner_loop(
+                Encoder::new(&*client),
+                updates_rx,
+                activity_rx,
+                requests_rx,
+                Keepalive::new(keepalive),
+                &mut sink,
+                std::io::sink(),
+            )
+        });
+
+        (BufReader::new(server), handle)
+    }
+
+    fn read_line(server: &mut BufReader<TcpStream>) -> String {
+        let mut line = String::new();
+        server.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+
+    #[test]
+    fn answers_pings() {
+        let (mut server, _handle) = start();
+        server.get_mut().write_all(b"PING :hello\r\n").unwrap();
+        assert_eq!(read_line(&mut server), "PONG :hello");
+    }
+
+    #[test]
+    fn pings_when_quiet_and_times_out() {
+        let (mut server, handle) = start();
+
+        assert!(read_line(&mut server).starts_with("PING "));
+        server.get_mut().write_all(b"PONG :readchat2\r\n").unwrap();
+
+        // the reply counted, so it pings again rather than giving up
+        assert!(read_line(&mut server).starts_with("PING "));
+        assert_eq!(read_line(&mut server), "QUIT :leaving");
+
+        let err = handle.join().unwrap().unwrap_err();
+        assert!(err.to_string().starts_with("timed out"), "{}", err);
+    }
 }
@@ -2,12 +2,13 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use cursive::{
     direction::Orientation,
+    event::Key,
     traits::{Nameable, Scrollable, View},
     view::{Margins, ScrollStrategy},
     views::*,
 };
 
-pub fn build_ui() -> impl View {
+pub fn build_ui(context: &Context) -> impl View {
     fn list_view() -> BoxedView {
         LimitedListView::limited_to(50)
             .scrollable()
@@ -26,11 +27,19 @@ pub fn build_ui() -> impl View {
             .boxed()
     }
 
-    fn tab_bar() -> impl View {
-        get_config()
+    fn stats_view() -> BoxedView {
+        TextView::new("")
+            .scrollable()
+            .scroll_x(false)
+            .scroll_y(true)
+            .boxed()
+    }
+
+    fn tab_bar(config: &Config) -> impl View {
+        config
             .tab_names
             .as_tabs()
-            .map(|s| s.as_styled_string(false))
+            .map(|s| s.as_styled_string(config, false, Unread::default()))
             .map(TextView::new)
             .map(|tv| PaddedView::new(Margins::lr(0, 1), tv))
             .fold(
@@ -41,24 +50,60 @@ pub fn build_ui() -> impl View {
             .with_name(TabBar::name())
     }
 
-    fn screens_view() -> impl View {
+    fn poll_view() -> impl View {
+        HideableView::new(TextView::new(""))
+            .hidden()
+            .boxed()
+            .with_name(PollView::name())
+    }
+
+    fn connection_view() -> impl View {
+        HideableView::new(TextView::new(""))
+            .hidden()
+            .boxed()
+            .with_name(ConnectionView::name())
+    }
+
+    fn command_prompt() -> impl View {
+        let prompt = LinearLayout::new(Orientation::Horizontal)
+            .child(TextView::new(":"))
+            .child(ResizedView::with_full_width(
+                EditView::new().on_submit(App::submit_command),
+            ));
+
+        let prompt = OnEventView::new(prompt)
+            .on_event(Key::Esc, |cursive| CommandPrompt::with(cursive).close());
+
+        HideableView::new(prompt)
+            .hidden()
+            .boxed()
+            .with_name(CommandPrompt::name())
+    }
+
+    fn screens_view(context: &Context) -> impl View {
         let mut screens = ScreensView::new();
         screens.add_active_screen(status_view().with_name(StatusView::name()));
         screens.add_screen(list_view().with_name(MessagesView::name()));
         screens.add_screen(list_view().with_name(LinksView::name()));
         screens.add_screen(list_view().with_name(HighlightsView::name()));
+        screens.add_screen(stats_view().with_name(StatsView::name()));
 
         LinearLayout::new(Orientation::Vertical)
-            .child(tab_bar())
-            .child(screens.boxed().with_name(MainScreen::name()))
+            .child(tab_bar(&context.config()))
+            .child(connection_view())
+            .child(poll_view())
+            .child(IgnoreKeys::new(
+                screens.boxed().with_name(MainScreen::name()),
+            ))
+            .child(command_prompt())
     }
 
-    screens_view()
+    screens_view(context)
 }
 
 pub(crate) fn next_unique_name() -> String {
     const PREFIX: &str = env!("CARGO_PKG_NAME");
-    thread_local! { static COUNTER: AtomicUsize = AtomicUsize::new(0); }
+    thread_local! { static COUNTER: AtomicUsize = const { AtomicUsize::new(0) }; }
     let n = std::thread::LocalKey::with(&COUNTER, move |c| c.fetch_add(1, Ordering::SeqCst));
     format!("{}_view_{}", PREFIX, n)
 }
@@ -95,16 +140,37 @@ pub(crate) use links_view::*;
 mod highlights_view;
 pub(crate) use highlights_view::*;
 
+mod stats_view;
+pub(crate) use stats_view::*;
+
+mod connection_view;
+pub(crate) use connection_view::*;
+
+mod poll_view;
+pub(crate) use poll_view::*;
+
+mod command_prompt;
+pub(crate) use command_prompt::*;
+
+mod help_view;
+pub(crate) use help_view::*;
+
+mod toggled;
+pub(crate) use toggled::Toggled;
+
 mod tab_bar;
 pub(crate) use tab_bar::*;
 
 mod status_view;
 pub(crate) use status_view::*;
 
+mod entry_view;
+pub(crate) use entry_view::EntryView;
+
 mod limited_list_view;
-use limited_list_view::LimitedListView;
+pub(crate) use limited_list_view::LimitedListView;
 
 mod spanned_appender;
 pub(crate) use spanned_appender::*;
 
-use crate::get_config;
+use crate::{App, Config, Context};
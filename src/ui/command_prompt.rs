@@ -0,0 +1,36 @@
+use cursive::{
+    views::{EditView, HideableView, LinearLayout, OnEventView, ResizedView},
+    Cursive,
+};
+
+use super::{MainScreen, OnView};
+
+/// A line at the bottom of the screen for typing commands, hidden until it is opened
+pub struct CommandPrompt<'c>(&'c mut Cursive);
+on_view! { CommandPrompt => HideableView<OnEventView<LinearLayout>> }
+
+impl<'c> CommandPrompt<'c> {
+    pub fn open(&mut self) {
+        self.on(|view| {
+            view.set_visible(true);
+            Self::edit_view(view).set_content("");
+            // the ':' in front of the input can't take the focus
+            let _ = view.get_inner_mut().get_inner_mut().set_focus_index(1);
+        });
+        let _ = self.cursive().focus_name(Self::name());
+    }
+
+    pub fn close(&mut self) {
+        self.on(|view| view.set_visible(false));
+        let _ = self.cursive().focus_name(MainScreen::name());
+    }
+
+    fn edit_view(view: &mut HideableView<OnEventView<LinearLayout>>) -> &mut EditView {
+        view.get_inner_mut()
+            .get_inner_mut()
+            .get_child_mut(1)
+            .and_then(|view| view.downcast_mut::<ResizedView<EditView>>())
+            .map(|view| view.get_inner_mut())
+            .expect("the prompt should have an EditView")
+    }
+}
@@ -0,0 +1,27 @@
+use cursive::{
+    theme::Style,
+    utils::span::SpannedString,
+    views::{HideableView, TextView},
+    Cursive,
+};
+
+use super::{OnView, SpannedAppender as _};
+
+/// Why we were disconnected, kept visible on every tab until we connect again
+pub struct ConnectionView<'c>(&'c mut Cursive);
+on_view! { ConnectionView => HideableView<TextView> }
+
+impl<'c> ConnectionView<'c> {
+    pub fn disconnected(&mut self, reason: &str) {
+        let text =
+            SpannedString::<Style>::plain("disconnected: ").append(reason, crate::Color::RED);
+        self.on(|view| {
+            view.get_inner_mut().set_content(text);
+            view.unhide()
+        })
+    }
+
+    pub fn connecting(&mut self) {
+        self.on(|view| view.hide())
+    }
+}
@@ -0,0 +1,69 @@
+use cursive::{view::ViewWrapper, views::BoxedView, View};
+
+use crate::{entry::Entry, Context};
+
+/// A row in one of the list views, keeping the [`Entry`] it was built from
+pub struct EntryView {
+    entry: Entry,
+    count: usize,
+    senders: Vec<String>,
+    last_seen: chrono::DateTime<chrono::Local>,
+    inner: BoxedView,
+}
+
+impl EntryView {
+    pub fn new(entry: Entry, view: impl View) -> Self {
+        Self {
+            last_seen: entry.ts,
+            entry,
+            count: 1,
+            senders: Vec::new(),
+            inner: BoxedView::boxed(view),
+        }
+    }
+
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+
+    pub fn last_seen(&self) -> chrono::DateTime<chrono::Local> {
+        self.last_seen
+    }
+
+    /// Counts `entry` as a repeat of this one, rather than adding a new row for it
+    pub fn repeat(&mut self, context: &Context, entry: &Entry) {
+        let name = entry.name.trim();
+        if name != self.entry.name.trim() && !self.senders.iter().any(|s| s == name) {
+            self.senders.push(name.to_string());
+        }
+
+        self.count += 1;
+        self.last_seen = entry.ts;
+        self.inner = BoxedView::boxed(self.entry.as_repeated_view(
+            context,
+            self.count,
+            &self.senders,
+        ))
+    }
+
+    pub fn set_view(&mut self, view: impl View) {
+        self.inner = BoxedView::boxed(view)
+    }
+
+    /// Replaces the row with `view`, unless it is showing repeats
+    pub fn restyle(&mut self, context: &Context, view: impl View) {
+        if self.count > 1 {
+            self.inner = BoxedView::boxed(self.entry.as_repeated_view(
+                context,
+                self.count,
+                &self.senders,
+            ));
+            return;
+        }
+        self.set_view(view)
+    }
+}
+
+impl ViewWrapper for EntryView {
+    cursive::wrap_impl!(self.inner: BoxedView);
+}
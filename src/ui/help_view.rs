@@ -0,0 +1,181 @@
+use cursive::{
+    event::Key,
+    theme::{Effect, Style},
+    traits::{Nameable, Scrollable},
+    utils::span::SpannedString,
+    views::{Dialog, OnEventView, ScrollView, TextView},
+    Cursive,
+};
+
+use super::{IntoBoxedView, MainScreen, OnView, SpannedAppender as _};
+use crate::{Action, App, Config};
+
+/// The keybinds from the configuration, shown over the rest of the screen
+pub struct HelpView<'c>(&'c mut Cursive);
+on_view! { HelpView => OnEventView<Dialog> }
+
+impl<'c> HelpView<'c> {
+    /// Actions that do the same thing on every tab
+    const GLOBAL: &'static [(Action, &'static str)] = &[
+        (Action::FocusStatusView, "focus status view"),
+        (Action::FocusMessagesView, "focus messages view"),
+        (Action::FocusLinksView, "focus links view"),
+        (Action::FocusHighlightsView, "focus highlights view"),
+        (Action::FocusStatsView, "focus stats view"),
+        (Action::NextTab, "next tab"),
+        (Action::PrevTab, "previous tab"),
+        (Action::ToggleTimestamp, "toggle timestamp"),
+        (Action::ToggleBadges, "toggle badges"),
+        (Action::TogglePoll, "start or end a poll"),
+        (Action::OpenPrompt, "open the command line"),
+        (Action::ReloadConfig, "reload the configuration"),
+        (Action::ToggleHelp, "show or hide this help"),
+        (Action::Quit, "quit"),
+    ];
+
+    pub fn is_open(&mut self) -> bool {
+        let name = Self::name();
+        self.cursive()
+            .screen_mut()
+            .find_layer_from_name(name)
+            .is_some()
+    }
+
+    pub fn open(&mut self) {
+        let text = self.render();
+        let dialog = Dialog::around(TextView::new(text).scrollable())
+            .title(crate::HEADER)
+            .dismiss_button("Close");
+
+        let view = OnEventView::new(dialog).on_event(Key::Esc, |cursive| {
+            HelpView::with(cursive).close();
+        });
+        self.cursive()
+            .add_layer(view.boxed().with_name(Self::name()));
+    }
+
+    pub fn close(&mut self) {
+        let screen = self.cursive().screen_mut();
+        if let Some(position) = screen.find_layer_from_name(Self::name()) {
+            screen.remove_layer(position);
+        }
+    }
+
+    /// Rebuilds the list when it is open, e.g. after the tab or the configuration changed
+    pub fn refresh(&mut self) {
+        if !self.is_open() {
+            return;
+        }
+
+        let text = self.render();
+        self.on(|view| {
+            if let Some(view) = view
+                .get_inner_mut()
+                .get_content_mut()
+                .downcast_mut::<ScrollView<TextView>>()
+            {
+                view.get_inner_mut().set_content(text)
+            }
+        })
+    }
+
+    fn active_tab(&mut self) -> usize {
+        MainScreen::with(self.cursive()).active_index()
+    }
+
+    fn render(&mut self) -> SpannedString<Style> {
+        let tab = self.active_tab();
+        let context = App::context(self.cursive());
+        let config = context.config();
+        Self::render_keybinds(&config, tab)
+    }
+
+    fn render_keybinds(config: &Config, tab: usize) -> SpannedString<Style> {
+        fn section(
+            text: SpannedString<Style>,
+            title: &str,
+            items: Vec<(String, &str)>,
+        ) -> SpannedString<Style> {
+            let text = text.append(format!("\n{}\n", title), Effect::Bold);
+            let width = items.iter().map(|(keys, _)| keys.chars().count()).max();
+            items.into_iter().fold(text, |text, (keys, description)| {
+                text.append_plain(format!(
+                    "  {:<width$}  {}\n",
+                    keys,
+                    description,
+                    width = width.unwrap_or_default()
+                ))
+            })
+        }
+
+        let sequences = config.keybinds.sequences();
+        let keys = |action: Action| {
+            let keys = sequences
+                .iter()
+                .filter(|(a, _)| *a == action)
+                .map(|(_, seq)| seq.to_string())
+                .collect::<Vec<_>>();
+            match keys.is_empty() {
+                true => "(unbound)".to_string(),
+                false => keys.join(", "),
+            }
+        };
+
+        let items = |actions: &[(Action, &'static str)]| {
+            actions
+                .iter()
+                .map(|&(action, description)| (keys(action), description))
+                .collect()
+        };
+
+        let title = config.tab_names.iter().nth(tab).unwrap_or_default();
+        let text = section(SpannedString::default(), "Keybinds", items(Self::GLOBAL));
+        section(
+            text,
+            &format!("On {}", title),
+            items(Self::tab_actions(tab)),
+        )
+    }
+
+    /// What the actions that depend on the tab do on it
+    fn tab_actions(tab: usize) -> &'static [(Action, &'static str)] {
+        match tab {
+            MainScreen::STATUS_VIEW_INDEX => &[
+                (Action::ScrollUp, "scroll up"),
+                (Action::ScrollDown, "scroll down"),
+                (Action::PageUp, "scroll up a page"),
+                (Action::PageDown, "scroll down a page"),
+                (Action::JumpTop, "scroll to the top"),
+                (
+                    Action::JumpBottom,
+                    "scroll to the bottom and follow new lines",
+                ),
+                (Action::ClearView, "clear the status lines"),
+            ],
+            MainScreen::STATS_VIEW_INDEX => &[
+                (Action::ScrollUp, "scroll up"),
+                (Action::ScrollDown, "scroll down"),
+                (Action::PageUp, "scroll up a page"),
+                (Action::PageDown, "scroll down a page"),
+                (Action::JumpTop, "scroll to the top"),
+                (Action::JumpBottom, "scroll to the bottom"),
+            ],
+            _ => &[
+                (Action::ScrollUp, "select the previous message"),
+                (Action::ScrollDown, "select the next message"),
+                (Action::PageUp, "select a page of messages up"),
+                (Action::PageDown, "select a page of messages down"),
+                (Action::JumpTop, "select the oldest message"),
+                (
+                    Action::JumpBottom,
+                    "clear the selection and follow new messages",
+                ),
+                (
+                    Action::IgnoreSelected,
+                    "ignore the user of the selected message",
+                ),
+                (Action::ClearView, "clear the messages"),
+            ],
+        }
+    }
+}
@@ -13,7 +13,7 @@ pub struct LimitedListView {
     list: VecDeque<Box<dyn View>>,
     heights: VecDeque<usize>,
     max: usize,
-    focus: usize,
+    focus: Option<usize>,
 }
 
 impl LimitedListView {
@@ -22,7 +22,7 @@ impl LimitedListView {
             list: VecDeque::with_capacity(max),
             heights: VecDeque::with_capacity(max),
             max,
-            focus: 0,
+            focus: None,
         }
     }
 
@@ -36,6 +36,7 @@ impl LimitedListView {
         while self.list.len() >= self.max {
             self.list.pop_front();
             self.heights.pop_front();
+            self.focus = self.focus.and_then(|focus| focus.checked_sub(1));
         }
         self.list.push_back(view);
         self.heights.push_back(0)
@@ -52,7 +53,7 @@ impl LimitedListView {
     pub fn clear(&mut self) {
         self.list.clear();
         self.heights.clear();
-        self.focus = 0
+        self.focus = None
     }
 
     pub fn len(&self) -> usize {
@@ -75,13 +76,37 @@ impl LimitedListView {
         head
     }
 
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&mut dyn View) -> bool,
+    {
+        let focus = self.focus.take();
+        let (mut list, mut heights) = <(VecDeque<_>, VecDeque<_>)>::default();
+
+        let iter = self.list.drain(..).zip(self.heights.drain(..));
+        for (i, (mut view, height)) in iter.enumerate() {
+            if !keep(&mut *view) {
+                continue;
+            }
+            if Some(i) == focus {
+                self.focus = Some(list.len())
+            }
+            list.push_back(view);
+            heights.push_back(height);
+        }
+
+        self.list = list;
+        self.heights = heights;
+    }
+
     pub fn visit_mut<F, R>(&mut self, focused: bool, source: Relative, each: F) -> Vec<R>
     where
         F: FnMut((usize, &mut dyn View)) -> R,
     {
+        let focus = self.focus.filter(|_| focused);
         match source {
             Relative::Front => {
-                let start = focused.then(|| self.focus).unwrap_or_default();
+                let start = focus.unwrap_or_default();
                 self.list
                     .iter_mut()
                     .map(DerefMut::deref_mut)
@@ -91,9 +116,7 @@ impl LimitedListView {
                     .collect()
             }
             Relative::Back => {
-                let end = focused
-                    .then(|| self.focus + 1)
-                    .unwrap_or_else(|| self.list.len());
+                let end = focus.map_or_else(|| self.list.len(), |focus| focus + 1);
                 self.list
                     .iter_mut()
                     .map(DerefMut::deref_mut)
@@ -106,25 +129,53 @@ impl LimitedListView {
         }
     }
 
-    pub fn move_focus(&mut self, delta: usize, direction: Direction) -> EventResult {
-        let _index = if let Some(index) =
-            direction.relative(Orientation::Vertical).and_then(|_pos| {
-                self.list
-                    .iter_mut()
-                    .enumerate()
-                    .skip(1)
-                    .filter_map(|(i, view)| view.take_focus(direction).then(|| i))
-                    .take(delta)
-                    .last()
-                // todo use pos
-            }) {
-            index
-        } else {
-            return EventResult::Ignored;
-        };
+    pub fn focus(&self) -> Option<usize> {
+        self.focus
+    }
+
+    pub fn focused_mut<V>(&mut self) -> Option<&mut V>
+    where
+        V: View,
+    {
+        self.list.get_mut(self.focus?)?.downcast_mut()
+    }
+
+    /// Moves the focus towards the oldest entry, starting from the newest one
+    pub fn focus_up(&mut self, delta: usize) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let old = self.focus;
+        let start = self.focus.unwrap_or(self.len());
+        self.focus = Some(start.saturating_sub(delta));
+        old != self.focus
+    }
+
+    /// Moves the focus towards the newest entry, clearing it after the last one
+    pub fn focus_down(&mut self, delta: usize) -> bool {
+        let old = self.focus;
+        self.focus = self
+            .focus
+            .map(|focus| focus.saturating_add(delta))
+            .filter(|&focus| focus < self.len());
+        old != self.focus
+    }
+
+    pub fn focus_first(&mut self) -> bool {
+        let old = self.focus;
+        self.focus = (!self.is_empty()).then_some(0);
+        old != self.focus
+    }
 
-        // TODO use index
-        EventResult::Consumed(None)
+    pub fn clear_focus(&mut self) -> bool {
+        self.focus.take().is_some()
+    }
+
+    fn row_area(&self, index: usize, width: usize) -> Rect {
+        let y = self.heights.iter().take(index).sum::<usize>();
+        let height = self.heights.get(index).copied().unwrap_or(1).max(1);
+        Rect::from_size((0, y), (width, height))
     }
 }
 
@@ -134,35 +185,43 @@ impl View for LimitedListView {
             return;
         }
 
+        let width = printer.size.x.saturating_sub(GUTTER);
+
         let mut y = 0;
         for (i, (child, &height)) in self.list.iter().zip(&self.heights).enumerate() {
+            let focused = Some(i) == self.focus;
+            if focused {
+                printer.print_vline((0, y), height, FOCUS_MARKER);
+            }
+
             child.draw(
                 &printer
-                    .offset((0, y))
-                    .cropped((printer.size.x, height))
-                    .focused(i == self.focus),
+                    .offset((GUTTER, y))
+                    .cropped((width, height))
+                    .focused(focused),
             );
             y += height
         }
     }
 
     fn layout(&mut self, size: Vec2) {
-        let available = size.x;
+        let available = size.map_x(|x| x.saturating_sub(GUTTER));
         self.heights.resize(self.list.len(), 0);
 
         for (child, height) in self.list.iter_mut().zip(&mut self.heights) {
-            *height = child.required_size(size).y;
-            child.layout(Vec2::new(available, *height))
+            *height = child.required_size(available).y;
+            child.layout(Vec2::new(available.x, *height))
         }
     }
 
     fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        let constraint = constraint.map_x(|x| x.saturating_sub(GUTTER));
         let vert = self
             .list
             .iter_mut()
             .map(|child| child.required_size(constraint));
 
-        Orientation::Vertical.stack(vert) + (1, 0)
+        Orientation::Vertical.stack(vert) + (GUTTER, 0)
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
@@ -170,34 +229,13 @@ impl View for LimitedListView {
             return EventResult::Ignored;
         }
 
-        let y: usize = self
-            .heights
-            .iter()
-            .take(self.heights.len() - self.focus)
-            .sum();
-        let offset = (1, y);
-
-        let result = self.list[self.focus].on_event(event.relativized(offset));
-        if result.is_consumed() {
-            return result;
-        }
-
-        use cursive::event::{Event::*, Key::*};
-
-        match event {
-            Key(Up) if self.focus > 0 => self.move_focus(1, Direction::down()),
-            Key(Down) if self.focus + 1 < self.len() => self.move_focus(1, Direction::up()),
-
-            Key(PageUp) => self.move_focus(10, Direction::down()),
-            Key(PageDown) => self.move_focus(10, Direction::up()),
-
-            Key(Home) | Ctrl(Home) => self.move_focus(usize::MAX, Direction::up()),
-            Key(End) | Ctrl(End) => self.move_focus(usize::MAX, Direction::up()),
-
-            Key(Tab) => self.move_focus(1, Direction::front()),
-            Shift(Tab) => self.move_focus(1, Direction::back()),
-
-            _ => EventResult::Ignored,
+        // moving the focus is done by the keybinds, see `MainScreen::scroll`
+        match self.focus {
+            Some(focus) => {
+                let offset = (GUTTER, self.row_area(focus, 0).top());
+                self.list[focus].on_event(event.relativized(offset))
+            }
+            None => EventResult::Ignored,
         }
     }
 
@@ -208,13 +246,14 @@ impl View for LimitedListView {
     }
 
     fn focus_view(&mut self, selector: &Selector<'_>) -> Result<(), ViewNotFound> {
-        self.focus = self
+        let focus = self
             .list
             .iter_mut()
             .enumerate()
             .find_map(|(i, v)| v.focus_view(selector).ok().map(|_| i))
             .ok_or(ViewNotFound)?;
 
+        self.focus = Some(focus);
         Ok(())
     }
 
@@ -223,7 +262,11 @@ impl View for LimitedListView {
             return (0, 0).into();
         }
 
-        let available = Vec2::new(size.x.saturating_sub(1), 0);
-        self.list[self.focus].important_area(available) + (1, self.focus)
+        // without a focus, the newest entry is the important one
+        let index = self.focus.unwrap_or(self.len() - 1);
+        self.row_area(index, size.x)
     }
 }
+
+const GUTTER: usize = 1;
+const FOCUS_MARKER: &str = "\u{258C}";
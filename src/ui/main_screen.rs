@@ -1,22 +1,103 @@
-use cursive::{views::*, Cursive};
+use cursive::{
+    direction::Direction,
+    event::{Event, EventResult},
+    view::{ScrollStrategy, ViewWrapper},
+    views::*,
+    Cursive, View,
+};
 
-use super::OnView;
+use super::{OnView, ScrollableList, StatsView};
 use crate::App;
 
+/// How far to move through the active screen
+#[derive(Copy, Clone, Debug)]
+pub enum Scroll {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
 pub struct MainScreen<'c>(&'c mut Cursive);
 on_view! { MainScreen => ScreensView<NamedView<BoxedView>> }
 
 impl<'c> MainScreen<'c> {
-    const STATUS_VIEW_INDEX: usize = 0;
-    const MESSAGE_VIEW_INDEX: usize = 1;
-    const LINKS_VIEW_INDEX: usize = 2;
-    const HIGHLIGHTS_VIEW_INDEX: usize = 3;
+    pub(crate) const STATUS_VIEW_INDEX: usize = 0;
+    pub(crate) const MESSAGE_VIEW_INDEX: usize = 1;
+    pub(crate) const LINKS_VIEW_INDEX: usize = 2;
+    pub(crate) const HIGHLIGHTS_VIEW_INDEX: usize = 3;
+    pub(crate) const STATS_VIEW_INDEX: usize = 4;
+    const SCREEN_COUNT: usize = 5;
+
+    /// How many entries a page is, in the lists
+    const PAGE: usize = 10;
 
     pub fn focus(&mut self, index: usize) {
+        if index == Self::STATS_VIEW_INDEX {
+            StatsView::with(self.cursive()).refresh();
+        }
         self.on(|view| view.set_active_screen(index));
         App::select_tab(self.cursive(), index);
     }
 
+    pub fn active_index(&mut self) -> usize {
+        self.on(|view| view.active_screen())
+    }
+
+    pub fn focus_next(&mut self) {
+        let index = self.active_index();
+        self.focus((index + 1) % Self::SCREEN_COUNT)
+    }
+
+    pub fn focus_previous(&mut self) {
+        let index = self.active_index();
+        self.focus((index + Self::SCREEN_COUNT - 1) % Self::SCREEN_COUNT)
+    }
+
+    /// Moves the selection in the lists, or scrolls the other screens
+    pub fn scroll(&mut self, scroll: Scroll) {
+        let changed = self.on_active_list(|view| {
+            let list = view.get_inner_mut();
+            match scroll {
+                Scroll::Up => list.focus_up(1),
+                Scroll::Down => list.focus_down(1),
+                Scroll::PageUp => list.focus_up(Self::PAGE),
+                Scroll::PageDown => list.focus_down(Self::PAGE),
+                Scroll::Top => list.focus_first(),
+                Scroll::Bottom => list.clear_focus(),
+            }
+        });
+
+        match changed {
+            Some(true) => App::follow_focus(self.cursive()),
+            Some(false) => {}
+            None => self.on(|view| {
+                let mut screen = match view.screen_mut() {
+                    Some(screen) => screen.get_mut(),
+                    None => return,
+                };
+                if let Some(view) = screen.downcast_mut::<ScrollView<ListView>>() {
+                    return scroll_view(view, scroll);
+                }
+                if let Some(view) = screen.downcast_mut::<ScrollView<TextView>>() {
+                    scroll_view(view, scroll)
+                }
+            }),
+        }
+    }
+
+    pub fn on_active_list<F, R>(&mut self, callback: F) -> Option<R>
+    where
+        F: FnOnce(&mut ScrollableList) -> R,
+    {
+        self.on(|view| {
+            let mut screen = view.screen_mut()?.get_mut();
+            screen.downcast_mut::<ScrollableList>().map(callback)
+        })
+    }
+
     pub fn focus_status_view(&mut self) {
         self.focus(Self::STATUS_VIEW_INDEX)
     }
@@ -32,4 +113,59 @@ impl<'c> MainScreen<'c> {
     pub fn focus_highlights_view(&mut self) {
         self.focus(Self::HIGHLIGHTS_VIEW_INDEX)
     }
+
+    pub fn focus_stats_view(&mut self) {
+        self.focus(Self::STATS_VIEW_INDEX)
+    }
+}
+
+fn scroll_view<V: View>(view: &mut ScrollView<V>, scroll: Scroll) {
+    let viewport = view.content_viewport();
+    let (top, height) = (viewport.top(), viewport.height());
+    let offset = match scroll {
+        Scroll::Up => top.saturating_sub(1),
+        Scroll::Down => top + 1,
+        Scroll::PageUp => top.saturating_sub(height),
+        Scroll::PageDown => top + height,
+        Scroll::Top => 0,
+        Scroll::Bottom => usize::MAX,
+    };
+    view.set_offset((0, offset));
+
+    // reaching the bottom follows new lines again
+    let strategy = match view.is_at_bottom() {
+        true => ScrollStrategy::StickToBottom,
+        false => ScrollStrategy::KeepRow,
+    };
+    view.set_scroll_strategy(strategy);
+}
+
+/// Leaves every key to the keybinds, rather than to the views on the main screen
+pub(crate) struct IgnoreKeys<V> {
+    view: V,
+}
+
+impl<V> IgnoreKeys<V> {
+    pub(crate) fn new(view: V) -> Self {
+        Self { view }
+    }
+}
+
+impl<V: View> ViewWrapper for IgnoreKeys<V> {
+    cursive::wrap_impl!(self.view: V);
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        use Event::*;
+        match event {
+            Char(..) | CtrlChar(..) | AltChar(..) | Key(..) | Shift(..) | Alt(..)
+            | AltShift(..) | Ctrl(..) | CtrlShift(..) | CtrlAlt(..) => EventResult::Ignored,
+            event => self.view.on_event(event),
+        }
+    }
+
+    // nothing on the main screen takes the focus, otherwise the layer would take tab to
+    // cycle the focus around, rather than leaving it to the keybinds
+    fn wrap_take_focus(&mut self, _: Direction) -> bool {
+        false
+    }
 }
@@ -0,0 +1,27 @@
+use cursive::{
+    views::{HideableView, TextView},
+    Cursive,
+};
+
+use super::OnView;
+use crate::App;
+
+/// The live tally of the running poll, hidden when there isn't one
+pub struct PollView<'c>(&'c mut Cursive);
+on_view! { PollView => HideableView<TextView> }
+
+impl<'c> PollView<'c> {
+    pub fn refresh(&mut self) {
+        let text = App::context(self.cursive())
+            .poll()
+            .as_ref()
+            .map(|poll| poll.render());
+
+        self.on(|view| {
+            view.set_visible(text.is_some());
+            if let Some(text) = text {
+                view.get_inner_mut().set_content(text)
+            }
+        })
+    }
+}
@@ -4,6 +4,7 @@ use super::{limited_list_view::LimitedListView, OnView};
 
 pub type ScrollableList = ScrollView<LimitedListView>;
 
+#[allow(dead_code)]
 pub trait ScrollToBottom<'c, T>
 where
     Self: OnView<'c, View = ScrollView<T>>,
@@ -6,6 +6,7 @@ pub trait SpannedAppender {
         T: Into<String>,
         S: Into<Style>;
 
+    #[allow(dead_code)]
     fn reversed<T>(self, text: T) -> Self
     where
         Self: Sized,
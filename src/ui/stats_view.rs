@@ -0,0 +1,17 @@
+use cursive::{
+    views::{ScrollView, TextView},
+    Cursive,
+};
+
+use super::OnView;
+use crate::App;
+
+pub struct StatsView<'c>(&'c mut Cursive);
+on_view! { StatsView => ScrollView<TextView> }
+
+impl<'c> StatsView<'c> {
+    pub fn refresh(&mut self) {
+        let text = App::context(self.cursive()).stats().render();
+        self.on(|view| view.get_inner_mut().set_content(text))
+    }
+}
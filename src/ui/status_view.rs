@@ -1,9 +1,10 @@
-use super::{OnView, SpannedAppender as _};
+use super::{MainScreen, OnView, SpannedAppender as _, TabBar};
 use crate::App;
 
 use cursive::{theme::Style, utils::span::SpannedString, views::*, Cursive};
 
 pub enum Status {
+    #[allow(dead_code)]
     Raw(String),
     Connecting,
     Connected,
@@ -11,6 +12,14 @@ pub enum Status {
     Pong,
     Joining(String),
     Joined(String),
+    Parting(String),
+    Parted(String),
+    Ignored(String),
+    Note(String),
+    Poll(String),
+    Reloaded,
+    Error(String),
+    Disconnected(String),
     Information,
 }
 
@@ -19,9 +28,17 @@ on_view! { StatusView => ScrollView<ListView> }
 
 impl<'c> StatusView<'c> {
     #[track_caller]
+    pub fn clear(&mut self) {
+        self.on(|view| view.get_inner_mut().clear())
+    }
+
     pub fn append(&mut self, status: Status) {
         type S = SpannedString<Style>;
 
+        // keepalives are too noisy to count as unread
+        let unread = !matches!(status, Status::Ping | Status::Pong);
+        let alert = matches!(status, Status::Error(..) | Status::Disconnected(..));
+
         let text = match status {
             Status::Connecting => S::plain("connecting to Twitch..."),
             Status::Connected => S::plain("connected!"),
@@ -29,6 +46,16 @@ impl<'c> StatusView<'c> {
             Status::Pong => S::plain("pong!"),
             Status::Joining(channel) => S::plain("joining: ").append(channel, crate::Color::TEAL),
             Status::Joined(channel) => S::plain("joined: ").append(channel, crate::Color::TEAL),
+            Status::Parting(channel) => S::plain("leaving: ").append(channel, crate::Color::TEAL),
+            Status::Parted(channel) => S::plain("left: ").append(channel, crate::Color::TEAL),
+            Status::Ignored(name) => S::plain("ignoring: ").append(name, crate::Color::TEAL),
+            Status::Note(note) => S::plain("note: ").append(note, crate::Color::TEAL),
+            Status::Poll(result) => S::plain("poll: ").append(result, crate::Color::TEAL),
+            Status::Reloaded => S::plain("configuration reloaded"),
+            Status::Error(error) => S::plain("error: ").append(error, crate::Color::RED),
+            Status::Disconnected(reason) => {
+                S::plain("disconnected: ").append(reason, crate::Color::RED)
+            }
             Status::Raw(..) => return, // ignore this
             Status::Information => return App::focus_messages_view(self.cursive()),
         };
@@ -36,6 +63,10 @@ impl<'c> StatusView<'c> {
         let ts = chrono::Local::now().format("[%c]").to_string();
         let view = TextView::new(text);
 
-        self.on(|inner| inner.get_inner_mut().add_child(&*ts, view))
+        self.on(|inner| inner.get_inner_mut().add_child(&ts, view));
+
+        if unread {
+            TabBar::with(self.cursive()).count_unread(MainScreen::STATUS_VIEW_INDEX, alert);
+        }
     }
 }
@@ -1,6 +1,6 @@
-use crate::get_config;
+use crate::App;
 
-use super::OnView as _;
+use super::{MainScreen, OnView as _};
 use cursive::{views::*, Cursive};
 
 pub struct TabBar<'c>(&'c mut Cursive);
@@ -8,8 +8,45 @@ on_view! { TabBar => LinearLayout }
 
 impl<'c> TabBar<'c> {
     pub(crate) fn select(&mut self, new: usize) {
+        App::context(self.cursive()).unread().remove(&new);
+        self.refresh(new)
+    }
+
+    /// Counts a new entry for the tab at `index`, unless it is the active one
+    ///
+    /// The tabs aren't redrawn, that is left for a [`TabBar::refresh_unread`] after the batch
+    pub(crate) fn count_unread(&mut self, index: usize, alert: bool) -> bool {
+        if index == MainScreen::with(self.cursive()).active_index() {
+            return false;
+        }
+
+        let context = App::context(self.cursive());
+        let mut unread = context.unread();
+        let unread = unread.entry(index).or_default();
+        unread.count += 1;
+        unread.alert |= alert;
+        true
+    }
+
+    /// Redraws the tabs with their unread counts
+    pub(crate) fn refresh_unread(&mut self) {
+        let active = MainScreen::with(self.cursive()).active_index();
+        self.refresh(active)
+    }
+
+    /// Forgets the unread entries for the tab at `index`
+    pub(crate) fn clear_unread(&mut self, index: usize) {
+        App::context(self.cursive()).unread().remove(&index);
+        let active = MainScreen::with(self.cursive()).active_index();
+        self.refresh(active)
+    }
+
+    fn refresh(&mut self, active: usize) {
+        let context = App::context(self.cursive());
+        let unread = context.unread().clone();
+
         self.on(|view| {
-            let config = get_config();
+            let config = context.config();
 
             for tab in config.tab_names.as_tabs() {
                 let view: &mut TextView = view
@@ -18,12 +55,16 @@ impl<'c> TabBar<'c> {
                     .map(|view: &mut PaddedView<_>| view.get_inner_mut())
                     .unwrap_or_else(|| panic!("cannot find TextView for {}", tab.index));
 
-                view.get_shared_content()
-                    .set_content(tab.as_styled_string(tab.index == new))
+                let unread = unread.get(&tab.index).copied().unwrap_or_default();
+                view.get_shared_content().set_content(tab.as_styled_string(
+                    &config,
+                    tab.index == active,
+                    unread,
+                ))
             }
         });
     }
 }
 
 mod tab;
-pub use tab::Tab;
+pub use tab::{Tab, Unread};
@@ -1,46 +1,70 @@
-use crate::{config::Tabs, ui::SpannedAppender as _, Action};
+use crate::{
+    config::{Bindings, Tabs},
+    ui::SpannedAppender as _,
+    Action, Config,
+};
 use cursive::{theme::Style, utils::span::SpannedString};
 
-use crate::get_config;
-
 pub struct Tab<'s> {
     pub index: usize,
     pub text: &'s str,
 }
 
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Unread {
+    pub count: usize,
+    pub alert: bool,
+}
+
 impl<'s> Tab<'s> {
-    pub fn as_styled_string(&self, focused: bool) -> SpannedString<Style> {
-        const CANONICAL_TAB_ORDER: [Action; 4] = [
+    pub fn as_styled_string(
+        &self,
+        config: &Config,
+        focused: bool,
+        unread: Unread,
+    ) -> SpannedString<Style> {
+        const CANONICAL_TAB_ORDER: [Action; 5] = [
             Action::FocusStatusView,
             Action::FocusMessagesView,
             Action::FocusLinksView,
             Action::FocusHighlightsView,
+            Action::FocusStatsView,
         ];
 
-        let config = get_config();
-        let Tabs { active, inactive } = config.tabs;
+        let Tabs {
+            active,
+            inactive,
+            unread: has_unread,
+            alert,
+        } = config.tabs;
 
         let name = config
             .keybinds
             .map
             .get(&CANONICAL_TAB_ORDER[self.index])
-            .expect("tab to exist")
-            .to_string();
-
-        if !focused {
-            return SpannedString::default()
-                .append(" ", inactive)
-                .append(name, inactive)
-                .append(". ", inactive)
-                .append(self.text, inactive)
-                .append(" ", inactive);
-        }
+            .and_then(Bindings::first)
+            .map(ToString::to_string)
+            // older configurations won't have bindings for newer tabs
+            .unwrap_or_else(|| self.index.to_string());
+
+        let style = match unread {
+            _ if focused => active,
+            Unread { alert: true, .. } => alert,
+            Unread { count, .. } if count > 0 => has_unread,
+            _ => inactive,
+        };
+
+        let count = match unread.count {
+            count if count > 0 && !focused => format!(" ({})", count),
+            _ => String::new(),
+        };
 
         SpannedString::default()
-            .append(" ", active)
-            .append(name, active)
-            .append(". ", active)
-            .append(self.text, active)
-            .append(" ", active)
+            .append(" ", style)
+            .append(name, style)
+            .append(". ", style)
+            .append(self.text, style)
+            .append(count, style)
+            .append(" ", style)
     }
 }
@@ -0,0 +1,62 @@
+use cursive::{view::ViewWrapper, Vec2, View};
+
+use crate::{Config, Context};
+
+/// Shows a view only while a configuration flag is set
+///
+/// The flag is read when the view is laid out, so toggling it doesn't have to visit every view
+pub(crate) struct Toggled<V> {
+    view: V,
+    context: Context,
+    flag: fn(&Config) -> bool,
+    laid_out: bool,
+}
+
+impl<V> Toggled<V> {
+    pub(crate) fn new(view: V, context: &Context, flag: fn(&Config) -> bool) -> Self {
+        Self {
+            view,
+            context: context.clone(),
+            flag,
+            laid_out: false,
+        }
+    }
+
+    fn visible(&self) -> bool {
+        (self.flag)(&self.context.config())
+    }
+}
+
+impl<V: View> ViewWrapper for Toggled<V> {
+    type V = V;
+
+    fn with_view<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&Self::V) -> R,
+    {
+        self.visible().then(|| f(&self.view))
+    }
+
+    fn with_view_mut<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Self::V) -> R,
+    {
+        if self.visible() {
+            Some(f(&mut self.view))
+        } else {
+            None
+        }
+    }
+
+    fn wrap_layout(&mut self, size: Vec2) {
+        self.laid_out = self.visible();
+        if self.laid_out {
+            self.view.layout(size)
+        }
+    }
+
+    fn wrap_needs_relayout(&self) -> bool {
+        let visible = self.visible();
+        visible != self.laid_out || (visible && self.view.needs_relayout())
+    }
+}